@@ -0,0 +1,293 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use macroquad::color::{Color, YELLOW};
+use macroquad::math::Vec2;
+use macroquad::text::Font;
+use crate::comet::Size;
+use crate::event::{Cause, GameEvent, Subscriber};
+use crate::ui::{Anchor, Ui};
+
+pub const ACHIEVEMENTS_PATH: &str = "res/achievements.txt";
+pub const PROGRESS_PATH: &str = "res/achievements.sav";
+
+// compiled in like the ships, so headless runs have achievements to work towards as well
+const BUILTIN_ACHIEVEMENTS: &str = include_str!("../res/achievements.txt");
+
+const TOAST_SECONDS: f32 = 4f32;
+const TOAST_BACKGROUND: Color = Color::new(0f32, 0f32, 0f32, 0.7f32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Goal {
+    Destroy { size: Option<Size>, sides: Option<u32> },
+    Shots,
+    Hits,
+    Waves,
+    LivesLost,
+    Survive,
+    Streak,
+}
+
+enum Step {
+    Add(f32),
+    Reset,
+    Keep,
+}
+
+impl Goal {
+    fn step(&self, event: &GameEvent) -> Step {
+        match (self, event) {
+            (Goal::Destroy { size, sides }, GameEvent::CometDestroyed { size: destroyed, sides: corners, by: Cause::Projectile, .. })
+                if size.as_ref().is_none_or(|size| size == destroyed) && sides.is_none_or(|sides| sides == *corners) => Step::Add(1f32),
+            (Goal::Shots, GameEvent::ProjectileFired { .. }) => Step::Add(1f32),
+            (Goal::Hits | Goal::Streak, GameEvent::CometDestroyed { by: Cause::Projectile, .. }) => Step::Add(1f32),
+            (Goal::Streak, GameEvent::ProjectileMissed { .. }) => Step::Reset,
+            (Goal::Waves, GameEvent::WaveCleared) => Step::Add(1f32),
+            (Goal::LivesLost, GameEvent::PlayerHit { life_lost: true, .. }) => Step::Add(1f32),
+            (Goal::Survive, GameEvent::Tick { delta_time, .. }) => Step::Add(*delta_time),
+            _ => Step::Keep,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Achievement {
+    pub id: String,
+    pub title: String,
+    pub text: String,
+    pub goal: Goal,
+    pub target: f32,
+    pub run: bool,  // progress starts over with every run
+}
+
+impl Achievement {
+    fn new(id: &str) -> Achievement {
+        Achievement {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            text: String::new(),
+            goal: Goal::Destroy { size: None, sides: None },
+            target: 1f32,
+            run: false,
+        }
+    }
+    pub fn is_per_run(&self) -> bool {
+        self.run || matches!(self.goal, Goal::Survive | Goal::Streak)
+    }
+
+    pub fn builtin() -> &'static [Achievement] {
+        static ACHIEVEMENTS: OnceLock<Vec<Achievement>> = OnceLock::new();
+        ACHIEVEMENTS.get_or_init(|| Achievement::parse(BUILTIN_ACHIEVEMENTS).expect("built in achievements are valid"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Achievement>, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Achievement::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(text: &str) -> Result<Vec<Achievement>, String> {
+        let mut achievements: Vec<Achievement> = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+            let (key, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            let result = match (key, achievements.last_mut()) {
+                ("achievement", _) if rest.is_empty() => Err("missing achievement id".to_string()),
+                ("achievement", _) => {
+                    achievements.push(Achievement::new(rest));
+                    Ok(())
+                }
+                (_, None) => Err(format!("'{}' before the first achievement", key)),
+                (_, Some(achievement)) => parse_entry(achievement, key, rest),
+            };
+            result.map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        Ok(achievements)
+    }
+}
+
+fn parse_number<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<f32, String> {
+    let word = words.next().ok_or("missing number")?;
+    word.parse().map_err(|_| format!("'{}' is not a number", word))
+}
+
+fn parse_entry(achievement: &mut Achievement, key: &str, rest: &str) -> Result<(), String> {
+    match key {
+        "title" => achievement.title = rest.to_string(),
+        "text" => achievement.text = rest.to_string(),
+        "goal" => {
+            let mut words = rest.split_whitespace();
+            let kind = words.next().ok_or("missing goal")?;
+            achievement.target = parse_number(&mut words)?;
+            let (mut size, mut sides) = (None, None);
+            while let Some(option) = words.next() {
+                match option {
+                    "size" => {
+                        let level = parse_number(&mut words)? as i64;
+                        size = Some(Size::from_level(level).ok_or(format!("size {} does not exist", level))?);
+                    }
+                    "sides" => sides = Some(parse_number(&mut words)? as u32),
+                    "run" => achievement.run = true,
+                    _ => return Err(format!("unknown goal option '{}'", option)),
+                }
+            }
+            achievement.goal = match kind {
+                "destroy" => Goal::Destroy { size, sides },
+                "shots" => Goal::Shots,
+                "hits" => Goal::Hits,
+                "waves" => Goal::Waves,
+                "lives_lost" => Goal::LivesLost,
+                "survive" => Goal::Survive,
+                "streak" => Goal::Streak,
+                _ => return Err(format!("unknown goal '{}'", kind)),
+            };
+        }
+        _ => return Err(format!("unknown achievement key '{}'", key)),
+    }
+    Ok(())
+}
+
+// what the current run added, kept apart so rewinding can take it back; it only joins the lifetime progress
+// once the run is over
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunProgress {
+    progress: Vec<f32>,
+    unlocked: Vec<bool>,
+}
+
+impl RunProgress {
+    fn new(count: usize) -> RunProgress {
+        RunProgress { progress: vec![0f32; count], unlocked: vec![false; count] }
+    }
+}
+
+// counts events towards every achievement, unlocks them and keeps the progress on disk
+pub struct AchievementTracker {
+    achievements: Vec<Achievement>,
+    progress: Vec<f32>,     // from finished runs
+    unlocked: Vec<bool>,
+    run: RunProgress,
+    toasts: Vec<(String, f32)>,
+    path: Option<String>,   // where progress is saved, nowhere for headless games
+}
+
+impl AchievementTracker {
+    pub fn new(achievements: Vec<Achievement>) -> AchievementTracker {
+        AchievementTracker {
+            progress: vec![0f32; achievements.len()],
+            unlocked: vec![false; achievements.len()],
+            run: RunProgress::new(achievements.len()),
+            achievements,
+            toasts: Vec::new(),
+            path: None,
+        }
+    }
+    pub fn get_achievements(&self) -> &[Achievement] { &self.achievements }
+    // both include the current run
+    pub fn is_unlocked(&self, idx: usize) -> bool { self.unlocked[idx] || self.run.unlocked[idx] }
+    pub fn get_progress(&self, idx: usize) -> f32 { self.progress[idx] + self.run.progress[idx] }
+    pub fn unlocked_count(&self) -> usize { (0..self.achievements.len()).filter(|idx| self.is_unlocked(*idx)).count() }
+    pub fn get_run_progress(&self) -> &RunProgress { &self.run }
+    pub fn set_run_progress(&mut self, run: RunProgress) { self.run = run }
+
+    // folds a finished run into the lifetime progress, per-run goals start from nothing anyway
+    pub fn commit_run(&mut self) {
+        for (idx, achievement) in self.achievements.iter().enumerate() {
+            self.unlocked[idx] |= self.run.unlocked[idx];
+            if !achievement.is_per_run() {
+                self.progress[idx] += self.run.progress[idx];
+            }
+        }
+        self.run = RunProgress::new(self.achievements.len());
+    }
+
+    // reads unlocks and lifetime progress, later saves go to the same file
+    pub fn load_progress(&mut self, path: &str) -> Result<(), String> {
+        self.path = Some(path.to_string());
+        if !Path::new(path).exists() { return Ok(()) }
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        for (line_idx, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let find = |id: &str| self.achievements.iter().position(|achievement| achievement.id == id);
+            // achievements that are no longer defined are dropped
+            match words.as_slice() {
+                ["unlocked", id] => if let Some(idx) = find(id) { self.unlocked[idx] = true },
+                ["progress", id, value] => if let Some(idx) = find(id) {
+                    self.progress[idx] = value.parse().map_err(|_| format!("{}: line {}: '{}' is not a number", path, line_idx + 1, value))?;
+                },
+                [] => (),
+                [word, ..] if word.starts_with('#') => (),
+                _ => return Err(format!("{}: line {}: unknown entry", path, line_idx + 1)),
+            }
+        }
+        Ok(())
+    }
+
+    // the current run is saved along, like the lifetime stats once a run is over
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else { return Ok(()) };
+        let mut text = String::from("# asteroids achievements\n");
+        for (idx, achievement) in self.achievements.iter().enumerate() {
+            match self.is_unlocked(idx) {
+                true => text += &format!("unlocked {}\n", achievement.id),
+                false if !achievement.is_per_run() && self.get_progress(idx) > 0f32 => {
+                    text += &format!("progress {} {}\n", achievement.id, self.get_progress(idx));
+                }
+                false => (),
+            }
+        }
+        fs::write(path, text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for (_, remaining) in self.toasts.iter_mut() {
+            *remaining -= delta_time;
+        }
+        self.toasts.retain(|(_, remaining)| *remaining > 0f32);
+    }
+
+    // newest toast at the bottom of the screen, older ones stacked above
+    pub fn draw_toasts(&self, font: &Font, view: Vec2) {
+        let mut ui = Ui::new(font, view, 0);
+        ui.anchored(Anchor::Bottom, Vec2::new(0f32, -20f32), 6f32, |ui| {
+            for (title, remaining) in self.toasts.iter() {
+                let mut color = YELLOW;
+                color.a = remaining.min(1f32);
+                ui.panel(8f32, TOAST_BACKGROUND, |ui| {
+                    ui.set_style(24, color);
+                    ui.label(&format!("ACHIEVEMENT UNLOCKED: {}", title));
+                });
+            }
+        });
+    }
+}
+
+// counts the events of the current run towards every achievement that is still locked,
+// a run that is left before it is over counts for nothing
+impl Subscriber for AchievementTracker {
+    fn handle(&mut self, event: &GameEvent) {
+        if *event == GameEvent::RunStarted {
+            self.run = RunProgress::new(self.achievements.len());
+        }
+        for (idx, achievement) in self.achievements.iter().enumerate() {
+            if self.unlocked[idx] || self.run.unlocked[idx] { continue }
+            match achievement.goal.step(event) {
+                Step::Add(amount) => self.run.progress[idx] += amount,
+                Step::Reset => self.run.progress[idx] = 0f32,
+                Step::Keep => (),
+            }
+            if self.progress[idx] + self.run.progress[idx] >= achievement.target {
+                self.run.unlocked[idx] = true;
+                self.toasts.push((achievement.title.clone(), TOAST_SECONDS));
+            }
+        }
+    }
+}
+
+impl Default for AchievementTracker {
+    fn default() -> AchievementTracker {
+        AchievementTracker::new(Achievement::builtin().to_vec())
+    }
+}
@@ -0,0 +1,64 @@
+use macroquad::math::Vec2;
+use crate::ecs::toroidal_offset;
+
+const SMOOTHING: f32 = 4f32;            // how fast the camera catches up, per second
+const LOOK_AHEAD_TIME: f32 = 0.4f32;    // seconds of the ship's velocity to look ahead
+const LOOK_AHEAD_DIR: f32 = 80f32;      // extra pixels in the direction the ship faces
+
+// follows the player through a toroidal world, pos is the centre of the view
+#[derive(Clone)]
+pub struct FollowCamera {
+    pos: Vec2,
+    prev_pos: Vec2,
+    view: Vec2,
+}
+
+impl FollowCamera {
+    pub fn new(pos: Vec2, view: Vec2) -> FollowCamera {
+        FollowCamera { pos, prev_pos: pos, view }
+    }
+    pub fn get_view(&self) -> Vec2 { self.view }
+    pub fn snap_to(&mut self, pos: Vec2) {
+        self.pos = pos;
+        self.prev_pos = pos;
+    }
+    pub fn store_previous(&mut self) {
+        self.prev_pos = self.pos;
+    }
+    pub fn get_pos(&self, alpha: f32) -> Vec2 {
+        self.prev_pos.lerp(self.pos, alpha)
+    }
+
+    pub fn update(&mut self, target: Vec2, vel: Vec2, dir: Vec2, world: Vec2, delta_time: f32) {
+        let goal = target + vel * LOOK_AHEAD_TIME + dir * LOOK_AHEAD_DIR;
+        // chase the goal the short way round, so the camera never sweeps across the whole world
+        let offset = toroidal_offset(goal - self.pos, world);
+        self.pos += offset * (1f32 - (-SMOOTHING * delta_time).exp());
+        let wrapped = self.pos - world * (self.pos / world).floor();
+        self.prev_pos += wrapped - self.pos;
+        self.pos = wrapped;
+    }
+
+    // true if pos (or anything within margin of it) is inside the view
+    pub fn can_see(&self, pos: Vec2, margin: f32, world: Vec2) -> bool {
+        let offset = toroidal_offset(pos - self.pos, world).abs();
+        offset.x < self.view.x / 2f32 + margin && offset.y < self.view.y / 2f32 + margin
+    }
+
+    // camera centres to draw the scene from so every copy of the world that is in view shows up
+    pub fn draw_targets(&self, world: Vec2, alpha: f32) -> Vec<Vec2> {
+        let pos = self.get_pos(alpha);
+        let (min, max) = (pos - self.view / 2f32, pos + self.view / 2f32);
+        let mut targets = Vec::new();
+        for ky in -1..=1 {
+            for kx in -1..=1 {
+                let shift = Vec2::new(kx as f32, ky as f32) * world;
+                let (copy_min, copy_max) = (shift, shift + world);
+                if min.x < copy_max.x && max.x > copy_min.x && min.y < copy_max.y && max.y > copy_min.y {
+                    targets.push(pos - shift);
+                }
+            }
+        }
+        targets
+    }
+}
@@ -1,51 +1,111 @@
+use std::f32::consts::PI;
+use macroquad::color::{GRAY, WHITE};
 use macroquad::math::Vec2;
-use macroquad::window::{screen_height, screen_width};
 use rand::{Rng};
+use crate::ecs::{wrap_position, Bounds, Lifetime, Renderable, Transform, Velocity, World};
 use crate::shape::Shape;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+// how far vertices of a spawned comet may sink below its radius, as a fraction of it
+pub const ROUGHNESS: f32 = 0.35f32;
+
+const SPREAD_SPEED: f32 = 60f32;    // pixels per second the two sides of a cut drift apart across the shot
+const MIN_AREA: f32 = 700f32;        // pixels², smaller pieces of a fractured comet crumble to debris
+const DEBRIS_SPEED: f32 = 50f32;    // pixels per second the edges of a crumbling piece fly apart
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub enum Size {
     One,
     Two,
     Three,
 }
 
+impl Size {
+    pub fn from_level(level: i64) -> Option<Size> {
+        match level {
+            1 => Some(Size::One),
+            2 => Some(Size::Two),
+            3 => Some(Size::Three),
+            _ => None,
+        }
+    }
+    pub fn get_level(&self) -> i64 {
+        match self {
+            Size::One => 1,
+            Size::Two => 2,
+            Size::Three => 3,
+        }
+    }
+    // size class of an arbitrary outline, the size whose spawn area is closest to its own by ratio
+    pub fn from_area(area: f32) -> Size {
+        let distance = |size: &Size| (area.max(1f32) / size.get_area()).ln().abs();
+        [Size::Three, Size::Two, Size::One].into_iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(Size::One)
+    }
+    // small rocks are harder to hit and worth more
+    pub fn get_points(&self) -> i32 {
+        match self {
+            Size::Three => 20,
+            Size::Two => 50,
+            Size::One => 100,
+        }
+    }
+    // default outline of a freshly spawned comet of this size, before the roughness is applied
+    pub fn get_sides_and_radius(&self) -> (u32, f32) {
+        match self {
+            Size::Three => (13, 50f32),
+            Size::Two => (11, 40f32),
+            Size::One => (8, 30f32),
+        }
+    }
+    // average area of a freshly spawned comet in pixels², the roughness sinks vertices by half of it on average
+    pub fn get_area(&self) -> f32 {
+        let (sides, radius) = self.get_sides_and_radius();
+        let radius = radius * (1f32 - ROUGHNESS / 2f32);
+        sides as f32 / 2f32 * radius * radius * (2f32 * PI / sides as f32).sin()
+    }
+}
+
 
+#[derive(Clone)]
 pub struct Comet {
     size: Size,
-    vel: Vec2,
-    rot_speed: f32,
+    vel: Vec2,          // pixels per second
+    rot_speed: f32,     // radians per second
     shape: Shape,
+    prev_pos: Vec2,
+    prev_rot: f32,
     alive: bool,
 }
 impl Comet {
 
-    pub fn new(size: u32, speed: f32) -> Comet{
-        let mut rng = rand::thread_rng();
-        let (start_pos, start_vel) = Comet::get_random_start_pos_and_vel();
-        Comet {
-            size: Size::Three,
-            vel: start_vel*speed,
-            rot_speed: rng.gen_range(-0.1f32..0.132),
-            shape: Shape::new(start_pos, size, size as f32*10f32, 2f32),
-            alive: true,
-        }
+    pub fn new(size: u32, speed: f32, arena: Vec2, rng: &mut impl Rng) -> Comet{
+        let (start_pos, start_vel) = Comet::get_random_start_pos_and_vel(arena, rng);
+        Comet::from_parts(Size::Three, start_vel*speed, rng.gen_range(-3f32..3.96),
+                          Shape::new(start_pos, size, size as f32*10f32, 2f32))
     }
 
-    pub fn spawn(size: Size, pos: Option<Vec2>) -> Comet {
-        let mut rng = rand::thread_rng();
-        let (default_pos, start_vel) = Comet::get_random_start_pos_and_vel();
+    pub fn spawn(size: Size, pos: Option<Vec2>, arena: Vec2, rng: &mut impl Rng) -> Comet {
+        let (default_pos, start_vel) = Comet::get_random_start_pos_and_vel(arena, rng);
         let start_pos = pos.unwrap_or(default_pos);
-        let (size, speed, rot_speed, radius, n_sides) = match size {
-            Size::Three => (Size::Three, 2f32, rng.gen_range(-0.05f32..0.0532), 50f32, 7u32),
-            Size::Two => (Size::Two, 3f32, rng.gen_range(-0.1f32..0.132), 40f32, 5u32),
-            Size::One => (Size::One, 5f32, rng.gen_range(-0.2f32..0.232), 30f32, 3u32),
+        let (n_sides, radius) = size.get_sides_and_radius();
+        let (size, speed, rot_speed) = match size {
+            Size::Three => (Size::Three, 60f32, rng.gen_range(-1.5f32..1.596)),
+            Size::Two => (Size::Two, 90f32, rng.gen_range(-3f32..3.96)),
+            Size::One => (Size::One, 150f32, rng.gen_range(-6f32..6.96)),
         };
+        let shape = Shape::asteroid(start_pos, n_sides, radius, ROUGHNESS, rng.gen(), 2f32);
+        Comet::from_parts(size, start_vel*speed, rot_speed, shape)
+    }
+
+    pub fn from_parts(size: Size, vel: Vec2, rot_speed: f32, shape: Shape) -> Comet {
         Comet {
             size,
-            vel: start_vel*speed,
+            vel,
             rot_speed,
-            shape: Shape::new(start_pos, n_sides, radius, 2f32),
+            prev_pos: shape.get_pos(),
+            prev_rot: shape.get_rot(),
+            shape,
             alive: true,
         }
     }
@@ -54,47 +114,151 @@ impl Comet {
     pub fn is_alive(&self) -> bool {self.alive}
     pub fn get_size(&self) -> Size { self.size.clone() }
     pub fn get_pos(&self) -> Vec2 { self.shape.get_pos() }
+    pub fn get_vel(&self) -> Vec2 { self.vel }
+    pub fn set_vel(&mut self, vel: Vec2) { self.vel = vel }
+    pub fn get_rot_speed(&self) -> f32 { self.rot_speed }
+    pub fn get_mass(&self) -> f32 { self.shape.area() }
     pub fn get_shape(&self) -> &Shape { &self.shape }
-    pub fn get_random_vel() -> Vec2 {
-        let mut rng = rand::thread_rng();
+    pub fn get_random_vel(rng: &mut impl Rng) -> Vec2 {
         Vec2::new(rng.gen_range(-1f32..1f32), rng.gen_range(-1f32..1f32)).normalize()
     }
-    pub fn get_random_start_pos_and_vel() -> (Vec2, Vec2) {
-        let mut rng = rand::thread_rng();
+    pub fn get_random_start_pos_and_vel(arena: Vec2, rng: &mut impl Rng) -> (Vec2, Vec2) {
         match rng.gen_range(0..4) {
-            0 => (Vec2::new(0f32, rng.gen_range(0f32..screen_height())),
+            0 => (Vec2::new(0f32, rng.gen_range(0f32..arena.y)),
                   Vec2::new(rng.gen_range(0f32..1f32), rng.gen_range(-1f32..1f32)).normalize()),
-            1 => (Vec2::new(rng.gen_range(0f32..screen_width()), 0f32),
+            1 => (Vec2::new(rng.gen_range(0f32..arena.x), 0f32),
                   Vec2::new(rng.gen_range(-1f32..1f32), rng.gen_range(0f32..1f32)).normalize()),
-            2 => (Vec2::new(screen_width(), rng.gen_range(0f32..screen_height())),
+            2 => (Vec2::new(arena.x, rng.gen_range(0f32..arena.y)),
                   Vec2::new(rng.gen_range(-1f32..0f32), rng.gen_range(-1f32..1f32)).normalize()),
-            3 => (Vec2::new(rng.gen_range(0f32..screen_width()), screen_height()),
+            3 => (Vec2::new(rng.gen_range(0f32..arena.x), arena.y),
                   Vec2::new(rng.gen_range(-1f32..1f32), rng.gen_range(-1f32..0f32)).normalize()),
             _ => panic!()
         }
     }
 
-    pub fn draw(&self) {
-        self.shape.draw();
+    pub fn draw(&self, alpha: f32) {
+        let pos = self.prev_pos.lerp(self.shape.get_pos(), alpha);
+        let rot = self.prev_rot + (self.shape.get_rot() - self.prev_rot) * alpha;
+        self.shape.draw_at(pos, rot, WHITE);
+    }
+    pub fn store_previous(&mut self) {
+        self.prev_pos = self.shape.get_pos();
+        self.prev_rot = self.shape.get_rot();
     }
 
-    pub fn update(&mut self) {
-        self.shape.add_pos(self.vel);
-        self.shape.add_rot(self.rot_speed);
+    pub fn update(&mut self, arena: Vec2, delta_time: f32) {
+        self.shape.add_pos(self.vel * delta_time);
+        self.shape.add_rot(self.rot_speed * delta_time);
 
         let puffer = self.shape.get_rad()*2f32;
-        let mut pos = self.shape.get_pos();
-        match (pos.x, pos.y) {
-            (w, _) if w <= 0f32-puffer => pos.x = screen_width()+puffer,
-            (w, _) if w >= screen_width()+puffer => pos.x = 0f32-puffer,
-            (_, h) if h <= 0f32-puffer => pos.y = screen_height()+puffer,
-            (_, h) if h >= screen_height()+puffer => pos.y = 0f32-puffer,
-            _ => ()
-        }
-        self.shape.set_pos(pos);
+        let pos = self.shape.get_pos();
+        let wrapped = wrap_position(pos, arena, puffer);
+        // move the previous position along so a wrap does not draw as a streak across the screen
+        self.prev_pos += wrapped - pos;
+        self.shape.set_pos(wrapped);
     }
     pub fn contains(&self, point: Vec2) -> bool {
         self.shape.contains(point)
     }
 
-}
\ No newline at end of file
+    // cuts the comet along the shot through impact_point; pieces big enough fly on as comets, the rest
+    // is returned separately to crumble, and together they keep the comet's momentum
+    pub fn fracture(&self, impact_point: Vec2, impact_dir: Vec2, rng: &mut impl Rng) -> (Vec<Comet>, Vec<Comet>) {
+        let mut shapes = self.shape.cut(impact_point, impact_dir);
+        if shapes.is_empty() {
+            shapes.push(self.shape.clone());
+        }
+        let masses: Vec<f32> = shapes.iter().map(|shape| shape.area()).collect();
+        let positions: Vec<Vec2> = shapes.iter().map(|shape| shape.get_pos()).collect();
+        let velocities = fragment_velocities(self.vel, impact_point, impact_dir, &masses, &positions);
+        shapes.into_iter().zip(velocities)
+            .map(|(shape, vel)| {
+                let rot_speed = self.rot_speed + rng.gen_range(-1f32..1f32);
+                Comet::from_parts(Size::from_area(shape.area()), vel, rot_speed, shape)
+            })
+            .partition(|piece| piece.get_mass() >= MIN_AREA)
+    }
+
+    // breaks the outline into its edges, which tumble away from the centre and fade out
+    pub fn crumble(&self, world: &mut World, rng: &mut impl Rng) {
+        let centre = self.get_pos();
+        let vertices = self.shape.get_world_vertices();
+        for (idx, a) in vertices.iter().enumerate() {
+            let b = vertices[(idx + 1) % vertices.len()];
+            let outwards = ((*a + b) / 2f32 - centre).normalize_or_zero();
+            let entity = world.spawn();
+            world.transforms.insert(entity, Transform::new(*a, Vec2::X.angle_between(b - *a)));
+            world.velocities.insert(entity, Velocity {
+                linear: self.vel + outwards * DEBRIS_SPEED * rng.gen_range(0.5f32..1.5f32),
+                angular: rng.gen_range(-4f32..4f32),
+            });
+            world.renderables.insert(entity, Renderable::Line { length: a.distance(b), color: GRAY });
+            world.lifetimes.insert(entity, Lifetime { remaining: rng.gen_range(0.5f32..1f32) });
+            world.bounds.insert(entity, Bounds::Wrap { margin: 0f32 });
+        }
+    }
+}
+
+// velocities that keep the momentum of a parent moving at vel: the fragments on either side of the cut through
+// impact_point drift apart perpendicular to the shot at SPREAD_SPEED, the lighter side faster, which adds up to nothing
+pub fn fragment_velocities(vel: Vec2, impact_point: Vec2, impact_dir: Vec2, masses: &[f32], positions: &[Vec2]) -> Vec<Vec2> {
+    let total: f32 = masses.iter().sum();
+    let Some(across) = impact_dir.perp().try_normalize() else { return vec![vel; positions.len()] };
+    if total <= 0f32 { return vec![vel; positions.len()] }
+    let sides: Vec<bool> = positions.iter().map(|pos| (*pos - impact_point).dot(across) >= 0f32).collect();
+    let positive: f32 = masses.iter().zip(sides.iter()).filter(|(_, side)| **side).map(|(mass, _)| *mass).sum();
+    sides.iter().map(|side| match side {
+        true => vel + across * SPREAD_SPEED * (total - positive) / total,
+        false => vel - across * SPREAD_SPEED * positive / total,
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn fragments_keep_the_parent_momentum() {
+        let vel = Vec2::new(40f32, -25f32);
+        let masses = [300f32, 120f32, 45f32];
+        let positions = [Vec2::new(10f32, 0f32), Vec2::new(-20f32, 15f32), Vec2::new(5f32, -30f32)];
+        let velocities = fragment_velocities(vel, Vec2::ZERO, Vec2::new(1f32, 1f32), &masses, &positions);
+        let momentum = masses.iter().zip(velocities.iter()).fold(Vec2::ZERO, |sum, (mass, vel)| sum + *vel * *mass);
+        let parent = vel * masses.iter().sum::<f32>();
+        assert!((momentum - parent).length() < 0.01f32, "{} != {}", momentum, parent);
+    }
+
+    #[test]
+    fn halves_are_a_size_smaller() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let comet = Comet::spawn(Size::Three, Some(Vec2::new(200f32, 200f32)), Vec2::new(800f32, 600f32), &mut rng);
+        let (pieces, _) = comet.fracture(comet.get_shape().centroid(), Vec2::new(0f32, 1f32), &mut rng);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces.iter().all(|piece| piece.get_size() == Size::Two), "{:?}", pieces.iter().map(Comet::get_size).collect::<Vec<_>>());
+        for size in [Size::One, Size::Two, Size::Three] {
+            assert_eq!(Size::from_area(size.get_area()), size);
+        }
+        assert_eq!(Size::from_area(0f32), Size::One);
+    }
+
+    #[test]
+    fn fragments_drift_apart_across_the_shot() {
+        // shot straight up through the origin, one piece on each side
+        let positions = [Vec2::new(-10f32, 5f32), Vec2::new(10f32, -5f32)];
+        let velocities = fragment_velocities(Vec2::ZERO, Vec2::ZERO, Vec2::new(0f32, -1f32), &[100f32, 300f32], &positions);
+        assert!(velocities[0].x < 0f32 && velocities[1].x > 0f32);
+        assert!(velocities.iter().all(|vel| vel.y.abs() < 0.001f32));
+        assert!((velocities[1].x - velocities[0].x - SPREAD_SPEED).abs() < 0.001f32);
+        assert!(velocities[0].length() > velocities[1].length());
+    }
+
+    #[test]
+    fn fragments_on_the_same_side_move_together() {
+        let positions = [Vec2::new(-10f32, -20f32), Vec2::new(-25f32, 10f32), Vec2::new(15f32, 0f32)];
+        let velocities = fragment_velocities(Vec2::ZERO, Vec2::ZERO, Vec2::new(0f32, 1f32), &[100f32, 100f32, 500f32], &positions);
+        assert_eq!(velocities[0], velocities[1]);
+        assert!(velocities[0].dot(velocities[2]) < 0f32);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+use macroquad::color::{Color, GRAY, WHITE};
+use macroquad::input::is_key_pressed;
+use macroquad::input::KeyCode::{Backspace, Down, Enter, Escape, Tab, Up};
+use macroquad::math::Vec2;
+use macroquad::prelude::{draw_line, draw_rectangle};
+use crate::comet::Size;
+use crate::ui::{Anchor, Ui};
+
+const OUTPUT_LINES: usize = 12;
+const HEIGHT: f32 = 300f32;
+const BACKGROUND: Color = Color::new(0f32, 0f32, 0f32, 0.85f32);
+
+const COMMANDS: [&str; 11] = ["spawn", "god", "lives", "score", "timescale", "pause", "step", "seed", "clear", "set", "help"];
+pub const SETTINGS: [&str; 9] = ["tick_rate", "scale_mode", "world_size", "debug", "time_scale", "rewind_seconds", "flight_model", "ship", "wells"];
+
+const HELP: [&str; 12] = [
+    "spawn comet <size 1-3> [x y]   spawn a comet, off camera or at x y",
+    "god                            toggle losing lives",
+    "lives <n>                      set remaining lives",
+    "score <n>                      set the score",
+    "timescale <factor>             slow down or speed up time",
+    "pause                          pause or resume the simulation",
+    "step [n]                       pause and run n physics ticks, 1 by default",
+    "seed <n>                       reseed the random generator",
+    "clear                          remove all comets, projectiles and entities",
+    "set <key> <value>              keys: tick_rate scale_mode world_size debug time_scale",
+    "                               rewind_seconds flight_model ship wells",
+    "help                           this list",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    SpawnComet { size: Size, pos: Option<Vec2> },
+    God,
+    Lives(u32),
+    Score(i32),
+    TimeScale(f32),
+    Pause,
+    Step(u32),
+    Seed(u64),
+    Clear,
+    Set { key: String, value: String },
+    Help,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["spawn", "comet", size] => Ok(Command::SpawnComet { size: parse_size(size)?, pos: None }),
+            ["spawn", "comet", size, x, y] => Ok(Command::SpawnComet {
+                size: parse_size(size)?,
+                pos: Some(Vec2::new(parse(x)?, parse(y)?)),
+            }),
+            ["god"] => Ok(Command::God),
+            ["lives", n] => Ok(Command::Lives(parse(n)?)),
+            ["score", n] => Ok(Command::Score(parse(n)?)),
+            ["timescale", factor] => Ok(Command::TimeScale(parse(factor)?)),
+            ["pause"] => Ok(Command::Pause),
+            ["step"] => Ok(Command::Step(1)),
+            ["step", n] => Ok(Command::Step(parse(n)?)),
+            ["seed", n] => Ok(Command::Seed(parse(n)?)),
+            ["clear"] => Ok(Command::Clear),
+            ["set", key, value] => Ok(Command::Set { key: key.to_string(), value: value.to_string() }),
+            ["help"] => Ok(Command::Help),
+            [] => Err(String::new()),
+            [word, ..] if COMMANDS.contains(word) => Err(format!("wrong arguments for {}, try help", word)),
+            [word, ..] => Err(format!("unknown command {}, try help", word)),
+        }
+    }
+    pub fn help() -> &'static [&'static str] {
+        &HELP
+    }
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("'{}' is not a valid number", word))
+}
+
+fn parse_size(word: &str) -> Result<Size, String> {
+    Size::from_level(parse(word)?).ok_or(format!("comet size {} does not exist", word))
+}
+
+// drop-down console, toggled by the caller and fed the typed characters of a frame
+pub struct Console {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    history_pos: Option<usize>,
+    output: VecDeque<String>,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_pos: None,
+            output: VecDeque::new(),
+        }
+    }
+    pub fn is_open(&self) -> bool { self.open }
+    pub fn toggle(&mut self) { self.open = !self.open }
+
+    pub fn print(&mut self, line: &str) {
+        self.output.push_back(line.to_string());
+        while self.output.len() > OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+
+    // returns a finished line once enter is pressed
+    pub fn inputs(&mut self, typed: &[char]) -> Option<String> {
+        for c in typed {
+            if !c.is_control() && *c != '`' && *c != '~' {
+                self.input.push(*c);
+            }
+        }
+        if is_key_pressed(Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(Escape) {
+            self.open = false;
+        }
+        if is_key_pressed(Tab) {
+            self.complete();
+        }
+        if is_key_pressed(Up) && !self.history.is_empty() {
+            let pos = self.history_pos.map_or(self.history.len() - 1, |pos| pos.saturating_sub(1));
+            self.history_pos = Some(pos);
+            self.input = self.history[pos].clone();
+        }
+        if is_key_pressed(Down) {
+            match self.history_pos {
+                Some(pos) if pos + 1 < self.history.len() => {
+                    self.history_pos = Some(pos + 1);
+                    self.input = self.history[pos + 1].clone();
+                }
+                _ => {
+                    self.history_pos = None;
+                    self.input.clear();
+                }
+            }
+        }
+        if is_key_pressed(Enter) && !self.input.trim().is_empty() {
+            let line = std::mem::take(&mut self.input);
+            self.print(&format!("> {}", line));
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+            }
+            self.history_pos = None;
+            return Some(line);
+        }
+        None
+    }
+
+    // completes the word under the cursor as far as it is unambiguous, lists the options otherwise
+    fn complete(&mut self) {
+        let (head, word) = match self.input.rfind(' ') {
+            Some(idx) => self.input.split_at(idx + 1),
+            None => ("", self.input.as_str()),
+        };
+        let options: &[&str] = match head.trim() {
+            "" => &COMMANDS,
+            "spawn" => &["comet"],
+            "set" => &SETTINGS,
+            "set scale_mode" => &["letterbox", "stretch", "integer"],
+            "set debug" => &["on", "off"],
+            "set flight_model" => &["classic", "newtonian", "twinstick"],
+            _ => &[],
+        };
+        let matches: Vec<&str> = options.iter().copied().filter(|option| option.starts_with(word)).collect();
+        let Some(first) = matches.first() else { return };
+        let common = matches.iter().fold(first.to_string(), |common, option| {
+            common.chars().zip(option.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+        });
+        let head = head.to_string();
+        if matches.len() == 1 {
+            self.input = format!("{}{} ", head, common);
+        } else {
+            self.print(&matches.join("  "));
+            self.input = format!("{}{}", head, common);
+        }
+    }
+
+    // output above the prompt, both growing up from the bottom of the bar
+    pub fn draw(&self, ui: &mut Ui) {
+        if !self.open { return }
+        let view = ui.get_view();
+        draw_rectangle(0f32, 0f32, view.x, HEIGHT, BACKGROUND);
+        draw_line(0f32, HEIGHT, view.x, HEIGHT, 1f32, GRAY);
+        let prompt = format!("> {}_", self.input);
+        ui.pinned(Anchor::BottomLeft, Vec2::new(20f32, HEIGHT - 12f32), 4f32, |ui| {
+            ui.set_style(18, GRAY);
+            for line in self.output.iter() {
+                ui.label(line);
+            }
+            ui.set_style(18, WHITE);
+            ui.label(&prompt);
+        });
+    }
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console::new()
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use macroquad::color::{Color, GREEN, RED, SKYBLUE, YELLOW};
+use macroquad::math::Vec2;
+use macroquad::prelude::{draw_circle, draw_circle_lines, draw_line};
+use crate::comet::Comet;
+use crate::player::Player;
+use crate::projectile::Projectile;
+use crate::ui::{Anchor, Ui};
+
+const LOG_LINES: usize = 8;
+const LOG_DURATION: f32 = 4f32;
+const VELOCITY_SCALE: f32 = 0.5f32;     // velocity arrows show where things are after this many seconds
+const TEXT_COLOR: Color = Color::new(0.6f32, 1f32, 0.6f32, 1f32);
+
+// toggleable overlay showing what the simulation sees: hitboxes, velocities, timings and collisions
+pub struct DebugOverlay {
+    enabled: bool,
+    log: VecDeque<(String, f32)>,
+    tick_time: f32,     // seconds spent in the last physics tick
+    frame_time: f32,
+}
+
+impl DebugOverlay {
+    pub fn new() -> DebugOverlay {
+        DebugOverlay {
+            enabled: false,
+            log: VecDeque::new(),
+            tick_time: 0f32,
+            frame_time: 0f32,
+        }
+    }
+    pub fn is_enabled(&self) -> bool { self.enabled }
+    pub fn toggle(&mut self) { self.enabled = !self.enabled }
+    pub fn record_tick_time(&mut self, seconds: f32) { self.tick_time = seconds }
+    pub fn record_frame_time(&mut self, seconds: f32) { self.frame_time = seconds }
+
+    pub fn log(&mut self, text: String) {
+        if !self.enabled { return }
+        self.log.push_back((text, LOG_DURATION));
+        while self.log.len() > LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+    pub fn age(&mut self, delta_time: f32) {
+        for (_, remaining) in self.log.iter_mut() {
+            *remaining -= delta_time;
+        }
+        self.log.retain(|(_, remaining)| *remaining > 0f32);
+    }
+
+    // drawn in world space at the positions of the last tick, not interpolated
+    pub fn draw_hitboxes(&self, player: &Player, comets: &[Comet], projectiles: &[Projectile]) {
+        let pos = player.get_pos();
+        match player.get_hit_outline() {
+            Some(outline) => for (idx, a) in outline.iter().enumerate() {
+                let b = outline[(idx + 1) % outline.len()];
+                draw_line(a.x, a.y, b.x, b.y, 1f32, SKYBLUE);
+            },
+            None => draw_circle_lines(pos.x, pos.y, player.get_hit_box_radius(), 1f32, SKYBLUE),
+        }
+        draw_arrow(pos, player.get_vel());
+        for comet in comets {
+            let pos = comet.get_pos();
+            draw_circle_lines(pos.x, pos.y, comet.get_shape().get_rad(), 1f32, RED);
+            draw_arrow(pos, comet.get_vel());
+        }
+        for projectile in projectiles {
+            let tip = projectile.get_tip_pos();
+            draw_circle(tip.x, tip.y, 3f32, YELLOW);
+        }
+    }
+
+    // stats lines on top, collision log below, starting at the bottom left corner of the HUD
+    pub fn draw_stats(&self, ui: &mut Ui, stats: &[String]) {
+        ui.anchored(Anchor::BottomLeft, Vec2::new(20f32, -36f32), 4f32, |ui| {
+            ui.set_style(16, TEXT_COLOR);
+            ui.label(&format!("tick {:.2} ms   frame {:.2} ms", self.tick_time * 1000f32, self.frame_time * 1000f32));
+            for line in stats {
+                ui.label(line);
+            }
+            for (text, _) in self.log.iter() {
+                ui.label(text);
+            }
+        });
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> DebugOverlay {
+        DebugOverlay::new()
+    }
+}
+
+fn draw_arrow(from: Vec2, vel: Vec2) {
+    let to = from + vel * VELOCITY_SCALE;
+    draw_line(from.x, from.y, to.x, to.y, 1f32, GREEN);
+}
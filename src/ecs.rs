@@ -0,0 +1,450 @@
+use macroquad::color::Color;
+use macroquad::math::Vec2;
+use macroquad::shapes::draw_line;
+
+use crate::shape::{circle_overlaps_polygon, polygon_contains, polygons_overlap};
+
+// handle to an entity, stale once the entity is despawned even if its slot gets reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub pos: Vec2,
+    pub rot: f32,
+    pub prev_pos: Vec2,
+    pub prev_rot: f32,
+}
+
+impl Transform {
+    pub fn new(pos: Vec2, rot: f32) -> Transform {
+        Transform { pos, rot, prev_pos: pos, prev_rot: rot }
+    }
+}
+
+// per second, like the rest of the physics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Velocity {
+    pub linear: Vec2,
+    pub angular: f32,
+}
+
+// what an entity is hit on, placed at its transform and turned with it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hitbox {
+    Point,
+    Circle { radius: f32 },
+    Polygon { vertices: Vec<Vec2> },
+}
+
+impl Hitbox {
+    // how far from the transform the hitbox can reach at most
+    pub fn get_reach(&self) -> f32 {
+        match self {
+            Hitbox::Point => 0f32,
+            Hitbox::Circle { radius } => *radius,
+            Hitbox::Polygon { vertices } => vertices.iter().map(|v| v.length()).fold(0f32, f32::max),
+        }
+    }
+    fn outline(vertices: &[Vec2], at: &Transform) -> Vec<Vec2> {
+        let turn = Vec2::from_angle(at.rot);
+        vertices.iter().map(|v| at.pos + turn.rotate(*v)).collect()
+    }
+    pub fn touches(&self, at: &Transform, other: &Hitbox, other_at: &Transform) -> bool {
+        let distance = (at.pos - other_at.pos).length();
+        if distance > self.get_reach() + other.get_reach() { return false }
+        match (self, other) {
+            (Hitbox::Point, Hitbox::Point) => false,
+            (Hitbox::Point, Hitbox::Circle { radius }) => distance < *radius,
+            (Hitbox::Point, Hitbox::Polygon { vertices }) => polygon_contains(&Hitbox::outline(vertices, other_at), at.pos),
+            (Hitbox::Circle { radius }, Hitbox::Circle { radius: other_radius }) => distance < radius + other_radius,
+            (Hitbox::Circle { radius }, Hitbox::Polygon { vertices }) => {
+                circle_overlaps_polygon(at.pos, *radius, &Hitbox::outline(vertices, other_at))
+            }
+            (Hitbox::Polygon { vertices }, Hitbox::Polygon { vertices: other_vertices }) => {
+                polygons_overlap(&Hitbox::outline(vertices, at), &Hitbox::outline(other_vertices, other_at))
+            }
+            _ => other.touches(other_at, self, at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider {
+    pub hitbox: Hitbox,
+    pub damage: u32,    // dealt to whatever of an opposing team it touches
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Renderable {
+    Line { length: f32, color: Color },
+}
+
+// seconds until the entity despawns on its own
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifetime {
+    pub remaining: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    pub hp: u32,
+    pub points: i32,    // score for destroying it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Team {
+    Player,
+    Hostile,
+    Neutral,
+}
+
+impl Team {
+    pub fn opposes(self, other: Team) -> bool {
+        matches!((self, other), (Team::Player, Team::Hostile) | (Team::Hostile, Team::Player))
+    }
+}
+
+// the game object an entity stands in for; the player, comets and projectiles move on their own and get
+// mirrored into the world every tick, so they collide with each other and with plain entities through the systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Body {
+    Player,
+    Comet(usize),       // index into the comets of this tick
+    Projectile(usize),  // index into the projectiles of this tick
+}
+
+// what happens at the arena border, the margin lets big objects leave the screen fully first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bounds {
+    Wrap { margin: f32 },
+}
+
+// wraps a position to the opposite side once it is `margin` past an edge
+pub fn wrap_position(mut pos: Vec2, arena: Vec2, margin: f32) -> Vec2 {
+    match (pos.x, pos.y) {
+        (w, _) if w <= 0f32-margin => pos.x = arena.x+margin,
+        (w, _) if w >= arena.x+margin => pos.x = 0f32-margin,
+        (_, h) if h <= 0f32-margin => pos.y = arena.y+margin,
+        (_, h) if h >= arena.y+margin => pos.y = 0f32-margin,
+        _ => ()
+    }
+    pos
+}
+
+// shortest way from one point to another in a world that wraps at its size
+pub fn toroidal_offset(offset: Vec2, world: Vec2) -> Vec2 {
+    offset - world * (offset / world).round()
+}
+
+pub fn is_outside(pos: Vec2, arena: Vec2, margin: f32) -> bool {
+    pos.x < 0f32-margin || pos.x > arena.x+margin || pos.y < 0f32-margin || pos.y > arena.y+margin
+}
+
+// component storage indexed by entity slot
+#[derive(Clone)]
+pub struct Components<T> {
+    items: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Components<T> {
+    fn new() -> Components<T> {
+        Components { items: Vec::new() }
+    }
+    pub fn insert(&mut self, entity: Entity, value: T) {
+        let index = entity.index as usize;
+        if self.items.len() <= index {
+            self.items.resize_with(index + 1, || None);
+        }
+        self.items[index] = Some((entity.generation, value));
+    }
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        self.get(entity)?;
+        self.items[entity.index as usize].take().map(|(_, value)| value)
+    }
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.items.get(entity.index as usize) {
+            Some(Some((generation, value))) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.items.get_mut(entity.index as usize) {
+            Some(Some((generation, value))) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.items.iter().enumerate().filter_map(|(index, slot)| {
+            slot.as_ref().map(|(generation, value)| (Entity { index: index as u32, generation: *generation }, value))
+        })
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.items.iter_mut().enumerate().filter_map(|(index, slot)| {
+            slot.as_mut().map(|(generation, value)| (Entity { index: index as u32, generation: *generation }, value))
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    pub transforms: Components<Transform>,
+    pub velocities: Components<Velocity>,
+    pub renderables: Components<Renderable>,
+    pub lifetimes: Components<Lifetime>,
+    pub bounds: Components<Bounds>,
+    pub colliders: Components<Collider>,
+    pub healths: Components<Health>,
+    pub teams: Components<Team>,
+    pub bodies: Components<Body>,
+}
+
+impl World {
+    pub fn new() -> World {
+        World {
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            transforms: Components::new(),
+            velocities: Components::new(),
+            renderables: Components::new(),
+            lifetimes: Components::new(),
+            bounds: Components::new(),
+            colliders: Components::new(),
+            healths: Components::new(),
+            teams: Components::new(),
+            bodies: Components::new(),
+        }
+    }
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity { index, generation: self.generations[index as usize] }
+            }
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+            }
+        }
+    }
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.alive.len() && self.alive[index] && self.generations[index] == entity.generation
+    }
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) { return }
+        self.transforms.remove(entity);
+        self.velocities.remove(entity);
+        self.renderables.remove(entity);
+        self.lifetimes.remove(entity);
+        self.bounds.remove(entity);
+        self.colliders.remove(entity);
+        self.healths.remove(entity);
+        self.teams.remove(entity);
+        self.bodies.remove(entity);
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] += 1;
+        self.free.push(entity.index);
+    }
+    pub fn spawn_body(&mut self, body: Body, transform: Transform, collider: Collider, team: Team, health: Option<Health>) -> Entity {
+        let entity = self.spawn();
+        self.bodies.insert(entity, body);
+        self.transforms.insert(entity, transform);
+        self.colliders.insert(entity, collider);
+        self.teams.insert(entity, team);
+        if let Some(health) = health { self.healths.insert(entity, health) }
+        entity
+    }
+    fn team_of(&self, entity: Entity) -> Team {
+        self.teams.get(entity).copied().unwrap_or(Team::Neutral)
+    }
+    fn is_spent(&self, entity: Entity) -> bool {
+        self.healths.get(entity).is_some_and(|health| health.hp == 0)
+    }
+    fn damage(&mut self, entity: Entity, amount: u32) {
+        if let Some(health) = self.healths.get_mut(entity) {
+            health.hp = health.hp.saturating_sub(amount);
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.alive.iter().filter(|alive| **alive).count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    // despawns everything, handles from before stay stale
+    pub fn clear(&mut self) {
+        for entity in self.entities() {
+            self.despawn(entity);
+        }
+    }
+    pub fn entities(&self) -> Vec<Entity> {
+        self.alive.iter().enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(index, _)| Entity { index: index as u32, generation: self.generations[index] })
+            .collect()
+    }
+}
+
+impl Default for World {
+    fn default() -> World {
+        World::new()
+    }
+}
+
+// two entities of opposing teams touching, with where each of them was
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub bodies: [Option<Body>; 2],
+    pub positions: [Vec2; 2],
+}
+
+impl Contact {
+    // the other side of a contact the body took part in
+    pub fn other(&self, body: Body) -> Option<(Option<Body>, Vec2)> {
+        match self.bodies {
+            [Some(a), _] if a == body => Some((self.bodies[1], self.positions[1])),
+            [_, Some(b)] if b == body => Some((self.bodies[0], self.positions[0])),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Destroyed {
+    pub body: Option<Body>,
+    pub pos: Vec2,
+    pub points: i32,
+}
+
+// what the world gets to see of the game each tick, and what the systems leave behind for it
+pub struct SystemContext {
+    pub arena: Vec2,
+    pub delta_time: f32,
+    pub contacts: Vec<Contact>,
+    pub destroyed: Vec<Destroyed>,
+}
+
+impl SystemContext {
+    pub fn new(arena: Vec2, delta_time: f32) -> SystemContext {
+        SystemContext { arena, delta_time, contacts: Vec::new(), destroyed: Vec::new() }
+    }
+}
+
+pub trait System: Send {
+    fn run(&mut self, world: &mut World, ctx: &mut SystemContext);
+}
+
+pub struct Movement;
+
+impl System for Movement {
+    fn run(&mut self, world: &mut World, ctx: &mut SystemContext) {
+        for (entity, vel) in world.velocities.iter() {
+            let Some(transform) = world.transforms.get_mut(entity) else { continue };
+            transform.pos += vel.linear * ctx.delta_time;
+            transform.rot += vel.angular * ctx.delta_time;
+            match world.bounds.get(entity) {
+                Some(Bounds::Wrap { margin }) => {
+                    let wrapped = wrap_position(transform.pos, ctx.arena, *margin);
+                    transform.prev_pos += wrapped - transform.pos;
+                    transform.pos = wrapped;
+                }
+                None => ()
+            }
+        }
+    }
+}
+
+pub struct Lifetimes;
+
+impl System for Lifetimes {
+    fn run(&mut self, world: &mut World, ctx: &mut SystemContext) {
+        let mut expired = Vec::new();
+        for (entity, lifetime) in world.lifetimes.iter_mut() {
+            lifetime.remaining -= ctx.delta_time;
+            if lifetime.remaining <= 0f32 { expired.push(entity) }
+        }
+        for entity in expired {
+            world.despawn(entity);
+        }
+    }
+}
+
+// opposing teams deal each other their damage on touch, whatever is already out of health is left alone
+pub struct Collisions;
+
+impl System for Collisions {
+    fn run(&mut self, world: &mut World, ctx: &mut SystemContext) {
+        let colliders: Vec<(Entity, Transform, Collider, Team)> = world.colliders.iter()
+            .filter_map(|(entity, collider)| {
+                world.transforms.get(entity).map(|transform| (entity, *transform, collider.clone(), world.team_of(entity)))
+            })
+            .collect();
+        for (idx, (a, a_at, a_col, a_team)) in colliders.iter().enumerate() {
+            for (b, b_at, b_col, b_team) in colliders.iter().skip(idx + 1) {
+                if !a_team.opposes(*b_team) || world.is_spent(*a) || world.is_spent(*b) { continue }
+                if !a_col.hitbox.touches(a_at, &b_col.hitbox, b_at) { continue }
+                world.damage(*a, b_col.damage);
+                world.damage(*b, a_col.damage);
+                ctx.contacts.push(Contact {
+                    bodies: [world.bodies.get(*a).copied(), world.bodies.get(*b).copied()],
+                    positions: [a_at.pos, b_at.pos],
+                });
+            }
+        }
+    }
+}
+
+// removes everything out of health and reports it with its points
+pub struct Reaper;
+
+impl System for Reaper {
+    fn run(&mut self, world: &mut World, ctx: &mut SystemContext) {
+        let dead: Vec<(Entity, i32)> = world.healths.iter()
+            .filter(|(_, health)| health.hp == 0)
+            .map(|(entity, health)| (entity, health.points))
+            .collect();
+        for (entity, points) in dead {
+            ctx.destroyed.push(Destroyed {
+                body: world.bodies.get(entity).copied(),
+                pos: world.transforms.get(entity).map_or(Vec2::ZERO, |transform| transform.pos),
+                points,
+            });
+            world.despawn(entity);
+        }
+    }
+}
+
+pub fn default_systems() -> Vec<Box<dyn System>> {
+    vec![Box::new(Movement), Box::new(Lifetimes), Box::new(Collisions), Box::new(Reaper)]
+}
+
+pub fn store_previous(world: &mut World) {
+    for (_, transform) in world.transforms.iter_mut() {
+        transform.prev_pos = transform.pos;
+        transform.prev_rot = transform.rot;
+    }
+}
+
+pub fn draw_world(world: &World, alpha: f32) {
+    for (entity, renderable) in world.renderables.iter() {
+        let Some(transform) = world.transforms.get(entity) else { continue };
+        let pos = transform.prev_pos.lerp(transform.pos, alpha);
+        let rot = transform.prev_rot + (transform.rot - transform.prev_rot) * alpha;
+        match *renderable {
+            Renderable::Line { length, color } => {
+                let tip = pos + Vec2::from_angle(rot) * length;
+                draw_line(pos.x, pos.y, tip.x, tip.y, 2f32, color);
+            }
+        }
+    }
+}
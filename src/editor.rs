@@ -0,0 +1,178 @@
+use macroquad::color::{GRAY, GREEN, SKYBLUE, WHITE, YELLOW};
+use macroquad::input::{is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, MouseButton};
+use macroquad::input::KeyCode::{Backspace, Delete, Enter, Equal, Escape, Key1, Key2, Key3, LeftBracket, Minus, L, O, P, Q, R, RightBracket, S, Tab, E, F, G};
+use macroquad::math::Vec2;
+use macroquad::prelude::draw_line;
+use crate::comet::Size;
+use crate::gravity::GravityWell;
+use crate::level::{CometSpec, Layout};
+use crate::player::Player;
+use crate::ui::{Anchor, Ui};
+
+pub const CUSTOM_LEVEL_PATH: &str = "res/levels/custom.level";
+
+const VELOCITY_SCALE: f32 = 0.5f32;    // velocity arrows show where a comet is after this many seconds
+const ROTATE_SPEED: f32 = 2f32;        // radians per second while Q/E is held
+const RESIZE_SPEED: f32 = 40f32;       // radius change per second while +/- is held
+const ROT_SPEED_STEP: f32 = 0.3f32;    // radians per second
+const MIN_RADIUS: f32 = 5f32;
+const WELL_STRENGTH: f32 = 4_000_000f32;
+const WELL_HORIZON: f32 = 20f32;
+
+const HELP: [&str; 5] = [
+    "LMB place/drag   RMB set velocity   1/2/3 size   P player spawn   G add/remove gravity well",
+    "Q/E rotate   +/- radius   [ ] sides   R/F spin   O new outline   DEL remove   TAB next",
+    "S save   L load   ENTER play   ESC menu",
+    "",
+    "",
+];
+
+pub enum EditorAction {
+    None,
+    Play(Layout),
+    Exit,
+}
+
+pub struct Editor {
+    layout: Layout,
+    selected: Option<usize>,
+    drag_offset: Option<Vec2>,
+    brush: Size,
+    status: String,
+}
+
+impl Editor {
+    pub fn new(arena: Vec2) -> Editor {
+        Editor::with_layout(Layout::new(arena / 2f32))
+    }
+    // carries on editing a level, e.g. the one that was just played
+    pub fn with_layout(layout: Layout) -> Editor {
+        Editor {
+            layout,
+            selected: None,
+            drag_offset: None,
+            brush: Size::Three,
+            status: String::new(),
+        }
+    }
+
+    fn comet_at(&self, point: Vec2) -> Option<usize> {
+        // last placed comet is drawn on top, so it wins
+        self.layout.comets.iter().rposition(|comet| (comet.pos - point).length() < comet.radius)
+    }
+
+    // called once per rendered frame, edge triggered keys would get lost at physics rate
+    // mouse is in playfield coordinates
+    pub fn inputs(&mut self, delta_time: f32, mouse: Vec2) -> EditorAction {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            match self.comet_at(mouse) {
+                Some(idx) => self.selected = Some(idx),
+                None => {
+                    self.layout.comets.push(CometSpec::new(self.brush.clone(), mouse, rand::random()));
+                    self.selected = Some(self.layout.comets.len() - 1);
+                }
+            }
+            self.drag_offset = self.selected.map(|idx| self.layout.comets[idx].pos - mouse);
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.drag_offset = None;
+        }
+        if is_key_pressed(P) {
+            self.layout.player_spawn = mouse;
+        }
+        if is_key_pressed(G) {
+            match self.layout.wells.iter().position(|well| (well.pos - mouse).length() < well.horizon * 2f32) {
+                Some(idx) => { self.layout.wells.remove(idx); }
+                None => self.layout.wells.push(GravityWell::new(mouse, WELL_STRENGTH, WELL_HORIZON)),
+            }
+        }
+        for (key, size) in [(Key1, Size::One), (Key2, Size::Two), (Key3, Size::Three)] {
+            if is_key_pressed(key) {
+                self.brush = size.clone();
+                if let Some(comet) = self.selected.map(|idx| &mut self.layout.comets[idx]) {
+                    (comet.sides, comet.radius) = size.get_sides_and_radius();
+                    comet.size = size;
+                }
+            }
+        }
+        if is_key_pressed(Tab) && !self.layout.comets.is_empty() {
+            self.selected = Some(self.selected.map_or(0, |idx| (idx + 1) % self.layout.comets.len()));
+        }
+
+        if let Some(idx) = self.selected {
+            let comet = &mut self.layout.comets[idx];
+            if let Some(offset) = self.drag_offset {
+                comet.pos = mouse + offset;
+            }
+            if is_mouse_button_down(MouseButton::Right) {
+                comet.vel = (mouse - comet.pos) / VELOCITY_SCALE;
+            }
+            if is_key_down(Q) { comet.rotation -= ROTATE_SPEED * delta_time }
+            if is_key_down(E) { comet.rotation += ROTATE_SPEED * delta_time }
+            if is_key_down(Equal) { comet.radius += RESIZE_SPEED * delta_time }
+            if is_key_down(Minus) { comet.radius = (comet.radius - RESIZE_SPEED * delta_time).max(MIN_RADIUS) }
+            if is_key_pressed(RightBracket) { comet.sides += 1 }
+            if is_key_pressed(LeftBracket) { comet.sides = (comet.sides - 1).max(3) }
+            if is_key_pressed(O) { comet.seed = rand::random() }
+            if is_key_pressed(R) { comet.rot_speed += ROT_SPEED_STEP }
+            if is_key_pressed(F) { comet.rot_speed -= ROT_SPEED_STEP }
+            if is_key_pressed(Delete) || is_key_pressed(Backspace) {
+                self.layout.comets.remove(idx);
+                self.selected = None;
+                self.drag_offset = None;
+            }
+        }
+
+        if is_key_pressed(S) {
+            self.status = match self.layout.save(CUSTOM_LEVEL_PATH) {
+                Ok(()) => format!("saved to {}", CUSTOM_LEVEL_PATH),
+                Err(err) => err,
+            };
+        }
+        if is_key_pressed(L) {
+            self.status = match Layout::load(CUSTOM_LEVEL_PATH) {
+                Ok(layout) => {
+                    self.layout = layout;
+                    self.selected = None;
+                    format!("loaded {}", CUSTOM_LEVEL_PATH)
+                }
+                Err(err) => err,
+            };
+        }
+        if is_key_pressed(Enter) {
+            return EditorAction::Play(self.layout.clone());
+        }
+        if is_key_pressed(Escape) {
+            return EditorAction::Exit;
+        }
+        EditorAction::None
+    }
+
+    pub fn draw(&self, ui: &mut Ui) {
+        for well in self.layout.wells.iter() {
+            well.draw();
+        }
+        for (idx, comet) in self.layout.comets.iter().enumerate() {
+            let color = if self.selected == Some(idx) { YELLOW } else { WHITE };
+            comet.get_shape().draw_colored(color);
+            let tip = comet.pos + comet.vel * VELOCITY_SCALE;
+            draw_line(comet.pos.x, comet.pos.y, tip.x, tip.y, 1f32, GREEN);
+        }
+        Player::at(self.layout.player_spawn).draw(SKYBLUE, 1f32);
+
+        let selection = match self.selected.map(|idx| &self.layout.comets[idx]) {
+            Some(comet) => format!("size {}  sides {}  radius {:.0}  vel {:.1} {:.1}  spin {:.2}",
+                                   comet.size.get_level(), comet.sides, comet.radius, comet.vel.x, comet.vel.y, comet.rot_speed),
+            None => format!("brush size {}", self.brush.get_level()),
+        };
+        let mut lines = HELP;
+        lines[3] = &selection;
+        lines[4] = &self.status;
+        ui.anchored(Anchor::TopLeft, Vec2::new(20f32, 14f32), 4f32, |ui| {
+            for (idx, line) in lines.iter().enumerate() {
+                ui.set_style(20, if idx < 3 { GRAY } else { WHITE });
+                ui.label(line);
+            }
+        });
+    }
+}
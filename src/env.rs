@@ -0,0 +1,365 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use macroquad::math::Vec2;
+use crate::ecs::toroidal_offset;
+use crate::game_state::{GamePhase, GameState, FPS};
+use crate::player::FlightModel;
+use crate::viewport::PLAYFIELD;
+
+const SHIP_FEATURES: usize = 5;
+const COMET_FEATURES: usize = 6;
+
+// one tick worth of controls, the same ones a player has on the keyboard
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Action {
+    pub turn: f32,  // -1 turns left, 1 turns right, 0 keeps heading
+    pub thrust: bool,
+    pub brake: bool,
+    pub fire: bool,
+}
+
+impl Action {
+    // number of discrete actions reachable through from_index
+    pub const COUNT: usize = 18;
+
+    // maps 0..COUNT onto every combination of turn (3) x thrust/brake/none (3) x fire (2)
+    pub fn from_index(index: usize) -> Action {
+        let index = index % Action::COUNT;
+        let turn = match index % 3 {
+            0 => 0f32,
+            1 => -1f32,
+            _ => 1f32,
+        };
+        let (thrust, brake) = match (index / 3) % 3 {
+            0 => (false, false),
+            1 => (true, false),
+            _ => (false, true),
+        };
+        Action { turn, thrust, brake, fire: index / 9 == 1 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObservationKind {
+    // the k closest comets relative to the ship, padded with zeros if there are fewer
+    NearestComets { k: usize },
+    // arena rasterised into width x height cells, 1 where a comet covers the cell centre
+    OccupancyGrid { width: usize, height: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnvConfig {
+    pub arena: Vec2,
+    pub tick_rate: u32,          // physics ticks per second, one step is one tick
+    pub flight_model: FlightModel,
+    pub observation: ObservationKind,
+    pub score_reward: f32,       // reward per point of score gained
+    pub life_loss_penalty: f32,  // subtracted for every life lost
+    pub step_reward: f32,        // given every tick the ship survives
+    pub max_seconds: Option<f32>,  // game time before an episode is cut off, whatever the tick rate
+}
+
+impl Default for EnvConfig {
+    fn default() -> EnvConfig {
+        EnvConfig {
+            arena: PLAYFIELD,
+            tick_rate: FPS as u32,
+            flight_model: FlightModel::Classic,
+            observation: ObservationKind::NearestComets { k: 8 },
+            score_reward: 0.1f32,
+            life_loss_penalty: 1f32,
+            step_reward: 0f32,
+            max_seconds: Some(60f32 * 5f32),
+        }
+    }
+}
+
+pub struct Env {
+    state: GameState,
+    config: EnvConfig,
+    steps: u32,
+    last_score: i32,
+    last_lives: u32,
+}
+
+impl Env {
+    // ready to step right away, as if reset with seed 0
+    pub fn new(config: EnvConfig) -> Env {
+        let mut env = Env {
+            state: GameState::headless(config.arena, 0),
+            config,
+            steps: 0,
+            last_score: 0,
+            last_lives: 0,
+        };
+        env.reset(0);
+        env
+    }
+    pub fn get_state(&self) -> &GameState { &self.state }
+    pub fn get_config(&self) -> &EnvConfig { &self.config }
+
+    pub fn reset(&mut self, seed: u64) -> Vec<f32> {
+        self.state = GameState::headless(self.config.arena, seed);
+        self.state.set_tick_rate(self.config.tick_rate);
+        self.state.set_flight_model(self.config.flight_model);
+        self.state.set_phase(GamePhase::PLAY);
+        self.state.reset();
+        self.steps = 0;
+        self.last_score = self.state.get_score();
+        self.last_lives = self.state.get_lives();
+        self.observation()
+    }
+
+    // applies the action for one physics tick, returns (observation, reward, done)
+    pub fn step(&mut self, action: Action) -> (Vec<f32>, f32, bool) {
+        if action.turn != 0f32 { self.state.turn(action.turn) }
+        if action.thrust { self.state.accelerate(1f32) }
+        if action.brake { self.state.accelerate(-0.5f32) }
+        if action.fire { self.state.shoot() }
+        self.state.refresh_all_cool_downs(self.state.get_time_per_tick());
+        self.state.tick();
+        self.steps += 1;
+
+        let score = self.state.get_score();
+        let lives = self.state.get_lives();
+        let mut reward = (score - self.last_score) as f32 * self.config.score_reward;
+        reward -= self.last_lives.saturating_sub(lives) as f32 * self.config.life_loss_penalty;
+        reward += self.config.step_reward;
+        self.last_score = score;
+        self.last_lives = lives;
+
+        let done = self.state.get_phase() == GamePhase::END
+            || self.config.max_seconds.is_some_and(|max| self.steps as f32 * self.state.get_time_per_tick() >= max);
+        (self.observation(), reward, done)
+    }
+
+    pub fn observation_len(&self) -> usize {
+        SHIP_FEATURES + match self.config.observation {
+            ObservationKind::NearestComets { k } => k * COMET_FEATURES,
+            ObservationKind::OccupancyGrid { width, height } => width * height,
+        }
+    }
+
+    // ship velocity, heading and spin followed by the configured comet features
+    pub fn observation(&self) -> Vec<f32> {
+        let player = self.state.get_player();
+        let mut obs = Vec::with_capacity(self.observation_len());
+        obs.extend_from_slice(&[player.get_vel().x, player.get_vel().y, player.get_dir().x, player.get_dir().y, player.get_ang_vel()]);
+        match self.config.observation {
+            ObservationKind::NearestComets { k } => self.nearest_comets(k, &mut obs),
+            ObservationKind::OccupancyGrid { width, height } => self.occupancy_grid(width, height, &mut obs),
+        }
+        obs
+    }
+
+    // per comet: present flag, offset and relative velocity, radius; offsets take the shortest way around the arena
+    fn nearest_comets(&self, k: usize, obs: &mut Vec<f32>) {
+        let arena = self.state.get_arena();
+        let player = self.state.get_player();
+        let mut comets: Vec<(Vec2, Vec2, f32)> = self.state.get_comets().iter()
+            .map(|comet| {
+                let offset = toroidal_offset(comet.get_pos() - player.get_pos(), arena);
+                (offset, comet.get_vel() - player.get_vel(), comet.get_shape().get_rad())
+            })
+            .collect();
+        comets.sort_by(|a, b| a.0.length_squared().total_cmp(&b.0.length_squared()));
+        for idx in 0..k {
+            match comets.get(idx) {
+                Some((offset, vel, radius)) => obs.extend_from_slice(&[
+                    1f32, offset.x / arena.x, offset.y / arena.y, vel.x, vel.y, radius / arena.x,
+                ]),
+                None => obs.extend_from_slice(&[0f32; COMET_FEATURES]),
+            }
+        }
+    }
+
+    fn occupancy_grid(&self, width: usize, height: usize, obs: &mut Vec<f32>) {
+        let cell = self.state.get_arena() / Vec2::new(width as f32, height as f32);
+        for y in 0..height {
+            for x in 0..width {
+                let centre = Vec2::new(x as f32 + 0.5f32, y as f32 + 0.5f32) * cell;
+                let occupied = self.state.get_comets().iter().any(|comet| comet.contains(centre));
+                obs.push(if occupied { 1f32 } else { 0f32 });
+            }
+        }
+    }
+}
+
+enum Job {
+    Reset(Vec<u64>),
+    Step(Vec<Action>),
+}
+
+enum Reply {
+    Reset(Vec<Vec<f32>>),
+    Step(Vec<(Vec<f32>, f32, bool)>),
+}
+
+// a thread that owns `count` of the environments for as long as the batch lives
+struct Worker {
+    jobs: Sender<Job>,
+    replies: Receiver<Reply>,
+    handle: JoinHandle<()>,
+    count: usize,
+}
+
+impl Worker {
+    fn spawn(count: usize, config: EnvConfig) -> Worker {
+        let (jobs, job_queue) = channel();
+        let (reply_queue, replies) = channel();
+        let handle = thread::spawn(move || {
+            let mut envs: Vec<Env> = (0..count).map(|_| Env::new(config)).collect();
+            // ends once the batch drops its end of the channel
+            for job in job_queue {
+                let reply = match job {
+                    Job::Reset(seeds) => Reply::Reset(envs.iter_mut().zip(seeds).map(|(env, seed)| env.reset(seed)).collect()),
+                    Job::Step(actions) => Reply::Step(envs.iter_mut().zip(actions).map(|(env, action)| env.step(action)).collect()),
+                };
+                if reply_queue.send(reply).is_err() { return }
+            }
+        });
+        Worker { jobs, replies, handle, count }
+    }
+}
+
+// a batch of independent environments, spread over one worker thread per core
+pub struct VecEnv {
+    workers: Vec<Worker>,
+    config: EnvConfig,
+}
+
+impl VecEnv {
+    pub fn new(count: usize, config: EnvConfig) -> VecEnv {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get()).min(count);
+        let workers = (0..threads)
+            .map(|idx| Worker::spawn(count / threads + usize::from(idx < count % threads), config))
+            .collect();
+        VecEnv { workers, config }
+    }
+    pub fn len(&self) -> usize { self.workers.iter().map(|worker| worker.count).sum() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn get_config(&self) -> &EnvConfig { &self.config }
+
+    pub fn reset(&mut self, seeds: &[u64]) -> Vec<Vec<f32>> {
+        assert_eq!(seeds.len(), self.len(), "one seed per environment");
+        self.dispatch(seeds, Job::Reset).into_iter()
+            .flat_map(|reply| match reply {
+                Reply::Reset(observations) => observations,
+                Reply::Step(_) => unreachable!("reset answered with a step"),
+            })
+            .collect()
+    }
+
+    // environments that report done are not reset automatically
+    pub fn step(&mut self, actions: &[Action]) -> Vec<(Vec<f32>, f32, bool)> {
+        assert_eq!(actions.len(), self.len(), "one action per environment");
+        self.dispatch(actions, Job::Step).into_iter()
+            .flat_map(|reply| match reply {
+                Reply::Step(results) => results,
+                Reply::Reset(_) => unreachable!("step answered with a reset"),
+            })
+            .collect()
+    }
+
+    // hands every worker its share, all of them run at once; the replies come back in environment order
+    fn dispatch<T: Clone>(&self, inputs: &[T], job: fn(Vec<T>) -> Job) -> Vec<Reply> {
+        let mut rest = inputs;
+        for worker in self.workers.iter() {
+            let (own, others) = rest.split_at(worker.count);
+            worker.jobs.send(job(own.to_vec())).expect("environment worker stopped");
+            rest = others;
+        }
+        self.workers.iter().map(|worker| worker.replies.recv().expect("environment worker stopped")).collect()
+    }
+}
+
+impl Drop for VecEnv {
+    fn drop(&mut self) {
+        for Worker { jobs, handle, .. } in self.workers.drain(..) {
+            drop(jobs);
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(count: usize) -> Vec<Action> {
+        (0..count).map(|idx| Action::from_index(idx * 7 % Action::COUNT)).collect()
+    }
+
+    #[test]
+    fn same_seed_and_actions_replay_the_same() {
+        let (mut a, mut b) = (Env::new(EnvConfig::default()), Env::new(EnvConfig::default()));
+        assert_eq!(a.reset(42), b.reset(42));
+        for action in actions(300) {
+            assert_eq!(a.step(action), b.step(action));
+        }
+    }
+
+    #[test]
+    fn observation_len_matches_the_observation() {
+        for observation in [ObservationKind::NearestComets { k: 5 }, ObservationKind::OccupancyGrid { width: 8, height: 6 }] {
+            let mut env = Env::new(EnvConfig { observation, ..EnvConfig::default() });
+            assert_eq!(env.observation().len(), env.observation_len());
+            let (obs, _, _) = env.step(Action::default());
+            assert_eq!(obs.len(), env.observation_len());
+        }
+    }
+
+    #[test]
+    fn step_before_reset_plays() {
+        let mut env = Env::new(EnvConfig::default());
+        assert_eq!(env.get_state().get_phase(), GamePhase::PLAY);
+        let first = env.observation();
+        let (obs, _, _) = env.step(Action { thrust: true, ..Action::default() });
+        assert_ne!(obs, first);
+    }
+
+    #[test]
+    fn losing_a_life_costs_the_penalty() {
+        let config = EnvConfig { score_reward: 0f32, step_reward: 0f32, life_loss_penalty: 2.5f32, ..EnvConfig::default() };
+        let mut env = Env::new(config);
+        let lives = env.get_state().get_lives();
+        // the ship is invincible for a moment after it spawns, so the comets keep coming until one costs a life
+        for _ in 0..300 {
+            let pos = env.get_state().get_player().get_pos();
+            env.state.execute(&format!("spawn comet 1 {} {}", pos.x, pos.y)).unwrap();
+            let (_, reward, _) = env.step(Action::default());
+            if env.get_state().get_lives() < lives {
+                assert_eq!(reward, -2.5f32);
+                return;
+            }
+            assert_eq!(reward, 0f32);
+        }
+        panic!("the comet never hit the ship");
+    }
+
+    #[test]
+    fn vec_env_matches_single_envs() {
+        let seeds = [1, 2, 3, 4, 5];
+        let mut batch = VecEnv::new(seeds.len(), EnvConfig::default());
+        let mut singles: Vec<Env> = seeds.iter().map(|_| Env::new(EnvConfig::default())).collect();
+        let observations: Vec<Vec<f32>> = singles.iter_mut().zip(seeds).map(|(env, seed)| env.reset(seed)).collect();
+        assert_eq!(batch.reset(&seeds), observations);
+        for _ in 0..20 {
+            let actions = actions(seeds.len());
+            let results: Vec<_> = singles.iter_mut().zip(actions.iter()).map(|(env, action)| env.step(*action)).collect();
+            assert_eq!(batch.step(&actions), results);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "one seed per environment")]
+    fn vec_env_reset_needs_a_seed_per_env() {
+        VecEnv::new(3, EnvConfig::default()).reset(&[1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "one action per environment")]
+    fn vec_env_step_needs_an_action_per_env() {
+        VecEnv::new(3, EnvConfig::default()).step(&[Action::default(); 4]);
+    }
+}
@@ -0,0 +1,51 @@
+use macroquad::math::Vec2;
+use crate::comet::Size;
+use crate::game_state::GamePhase;
+
+// what destroyed a comet or hit the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cause {
+    Projectile,
+    Comet,
+    Well,
+    Entity,     // anything spawned into the entity world
+}
+
+// something that happened during a tick, positions are in world coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    RunStarted,
+    PhaseChanged { from: GamePhase, to: GamePhase },
+    Tick { delta_time: f32, distance: f32 },    // game time passed in PLAY and how far the player flew
+    ProjectileFired { pos: Vec2, dir: Vec2 },
+    ProjectileMissed { pos: Vec2 },
+    CometDestroyed { size: Size, sides: u32, pos: Vec2, by: Cause },  // pos is the impact point for projectiles
+    PlayerHit { from: Vec2, by: Cause, life_lost: bool },
+    EntityDestroyed { pos: Vec2, points: i32 },
+    WaveCleared,
+}
+
+// anything that reacts to the events of a tick, after the tick is done
+pub trait Subscriber: Send {
+    fn handle(&mut self, event: &GameEvent);
+}
+
+// events of the running tick, in the order they happened
+#[derive(Default)]
+pub struct EventQueue {
+    events: Vec<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn new() -> EventQueue {
+        EventQueue { events: Vec::new() }
+    }
+    pub fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+    pub fn len(&self) -> usize { self.events.len() }
+    pub fn is_empty(&self) -> bool { self.events.is_empty() }
+}
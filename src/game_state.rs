@@ -1,28 +1,56 @@
-use std::fmt::{Display, format, Formatter};
-use macroquad::color::{BLACK, GRAY, WHITE};
-use macroquad::input::{is_key_down};
-use macroquad::input::KeyCode::{A, C, D, Down, Enter, Left, Right, S, Space, Up, W};
-use macroquad::prelude::{clear_background, draw_text_ex, TextParams};
-use macroquad::text::{Font, measure_text};
-use macroquad::window::screen_width;
+use macroquad::color::{Color, GRAY, PURPLE, WHITE, YELLOW};
+use std::time::Instant;
+use macroquad::input::{get_char_pressed, is_key_down, is_key_pressed};
+use macroquad::input::KeyCode::{Equal, F, F3, GraveAccent, Key0, Minus, N, R};
+use macroquad::math::Vec2;
+use macroquad::prelude::{draw_circle, draw_rectangle_lines};
+use macroquad::text::Font;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::achievement::{Achievement, AchievementTracker, ACHIEVEMENTS_PATH, PROGRESS_PATH};
+use crate::camera::FollowCamera;
 use crate::comet::{Comet, Size};
-use crate::player::Player;
+use crate::console::{Command, Console, SETTINGS};
+use crate::debug::DebugOverlay;
+use crate::ecs::{default_systems, draw_world, is_outside, store_previous, toroidal_offset, Body, Collider, Entity, Health, Hitbox, System,
+                 SystemContext, Team, Transform, World};
+use crate::event::{Cause, EventQueue, GameEvent, Subscriber};
+use crate::gravity::{total_acceleration, GravityWell};
+use crate::highscore::{HighScore, HighScores, HIGH_SCORES_PATH};
+use crate::gamepad::Gamepads;
+use crate::input::{Action, InputState};
+use crate::level::Layout;
+use crate::player::{FlightModel, Player};
 use crate::projectile::Projectile;
+use crate::rewind::{RewindBuffer, Snapshot, DEFAULT_REWIND_SECONDS};
+use crate::scoring::Scoring;
+use crate::stats::{LifetimeStats, RunStats, STATS_PATH};
+use crate::ship::{ShipClass, SHIPS_PATH};
+use crate::script::{LevelScript, ScriptCommand, ScriptView};
+use crate::ui::{Anchor, Ui, DEFAULT_SIZE};
+use crate::viewport::{ScaleMode, Viewport, PLAYFIELD};
 
-const ROTATION_SPEED: f32 = 0.15f32;
-const PROJECTILE_SPEED: f32 = 30f32;
-const SHOOTING_COOLDOWN: f32 = 0.2f32;
-const SPLIT_RATIO: u32 = 2;
-const COLLISION_KNOCK_BACK: f32 = 10f32;
+pub const FPS: i32 = 30;    //default fps for physics, see set_tick_rate
+pub const TIME_PER_FRAME: f32 = 1f32 / FPS as f32;
+
+// motion is in pixels and radians per second, so the tick rate does not change gameplay
+const COLLISION_KNOCK_BACK: f32 = 9000f32;   // per second of contact
 
 const BASE_COMET_SPAWN_RATE: f32 = 10f32;
+const OFF_CAMERA_SPAWN_MARGIN: f32 = 100f32;
+const RADAR_WIDTH: f32 = 200f32;
 const INVINCIBILITY_DURATION: f32 = 1f32;
+const MIN_TIME_SCALE: f32 = 0.05f32;
+const MAX_TIME_SCALE: f32 = 8f32;
+const FAST_FORWARD: f32 = 4f32;   // on top of the time scale while F is held
 
-#[derive(Debug)]
+// what the simulation is doing, what is on screen is up to the scenes
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum GamePhase {
-    MENU,
+    MENU,   // idle, nothing ticks
     PLAY,
-    END,
+    END,    // the run is over but can still be rewound
 }
 
 
@@ -31,193 +59,736 @@ pub struct GameState {
     player: Player,
     comets: Vec<Comet>,
     projectiles: Vec<Projectile>,
-    font: Font,
-    score: i32,
+    font: Option<Font>,
+    arena: Vec2,    // size of the world, bigger than the viewport when scrolling
+    viewport: Viewport,
+    camera: FollowCamera,
+    scrolling: bool,
+    rng: StdRng,
+    time_per_tick: f32,
+    scoring: Scoring,
     weapon_cd: f32,
     player_lives: u32,
     comet_spawn_timer: f32,
     game_duration: f32,
     invincibility_timer: f32,
+    script: Option<LevelScript>,
+    messages: Vec<(String, f32)>,
+    custom_level: Option<Layout>,
+    world: World,
+    systems: Vec<Box<dyn System>>,
+    debug: DebugOverlay,
+    console: Console,
+    typed: Vec<char>,   // characters typed this frame while the console was closed, for text inputs
+    typing: bool,       // a text input has the keyboard, letters do not rewind
+    input: InputState,
+    gamepads: Gamepads,
+    god_mode: bool,
+    time_scale: f32,    // game seconds per real second
+    paused: bool,
+    pending_steps: u32,     // ticks to run while paused
+    fast_forward: bool,
+    rewind: RewindBuffer,
+    rewinding: bool,
+    flight_model: FlightModel,
+    ships: Vec<ShipClass>,
+    ship: usize,    // index of the selected ship class
+    wells: Vec<GravityWell>,
+    random_wells: u32,  // placed anywhere at the start of every run without a custom level
+    stats: RunStats,
+    lifetime: LifetimeStats,    // finished runs before the current one
+    stats_path: Option<String>, // where lifetime stats are kept, headless games keep none
+    run_over: bool,             // the current run ended and counts towards the lifetime stats
+    high_scores: HighScores,    // like the lifetime stats, without the current run
+    high_scores_path: Option<String>,
+    player_name: String,        // goes with the next high scores, kept from run to run
+    achievements: AchievementTracker,
+    events: EventQueue,
+    subscribers: Vec<Box<dyn Subscriber>>,  // added from outside, told after the built-in ones
 }
 
 use GamePhase::*;
 
 impl GameState {
     pub fn new(font: Font) -> GameState {
+        let mut game_state = GameState::with_parts(Some(font), PLAYFIELD, StdRng::from_entropy());
+        game_state.gamepads = Gamepads::new();
+        match ShipClass::load(SHIPS_PATH) {
+            Ok(ships) => game_state.ships = ships,
+            Err(err) => eprintln!("could not load ship classes, using the built in ones: {}", err),
+        }
+        match LifetimeStats::load(STATS_PATH) {
+            Ok(lifetime) => game_state.lifetime = lifetime,
+            Err(err) => eprintln!("could not load lifetime stats, starting over: {}", err),
+        }
+        game_state.stats_path = Some(STATS_PATH.to_string());
+        match HighScores::load(HIGH_SCORES_PATH) {
+            Ok(high_scores) => game_state.high_scores = high_scores,
+            Err(err) => eprintln!("could not load high scores, starting over: {}", err),
+        }
+        game_state.high_scores_path = Some(HIGH_SCORES_PATH.to_string());
+        match Achievement::load(ACHIEVEMENTS_PATH) {
+            Ok(achievements) => game_state.achievements = AchievementTracker::new(achievements),
+            Err(err) => eprintln!("could not load achievements, using the built in ones: {}", err),
+        }
+        if let Err(err) = game_state.achievements.load_progress(PROGRESS_PATH) {
+            eprintln!("could not load achievement progress: {}", err);
+        }
+        game_state
+    }
+    // game state without window or font, for simulations driven from code
+    pub fn headless(arena: Vec2, seed: u64) -> GameState {
+        GameState::with_parts(None, arena, StdRng::seed_from_u64(seed))
+    }
+    fn with_parts(font: Option<Font>, arena: Vec2, rng: StdRng) -> GameState {
         GameState {
-            player: Player::new(),
+            player: Player::new(arena),
             comets: Vec::new(),
             projectiles: Vec::new(),
             font,
-            score: 0,
+            arena,
+            viewport: Viewport::new(PLAYFIELD, ScaleMode::Letterbox),
+            camera: FollowCamera::new(arena / 2f32, PLAYFIELD),
+            scrolling: arena.x > PLAYFIELD.x || arena.y > PLAYFIELD.y,
+            rng,
+            time_per_tick: TIME_PER_FRAME,
+            scoring: Scoring::new(),
             weapon_cd: 0f32,
             player_lives: 3,
             game_state: MENU,
             comet_spawn_timer: 0f32,
             game_duration: 0f32,
             invincibility_timer: 1f32,
+            script: None,
+            messages: Vec::new(),
+            custom_level: None,
+            world: World::new(),
+            systems: default_systems(),
+            debug: DebugOverlay::new(),
+            console: Console::new(),
+            typed: Vec::new(),
+            typing: false,
+            input: InputState::new(),
+            gamepads: Gamepads::none(),
+            god_mode: false,
+            time_scale: 1f32,
+            paused: false,
+            pending_steps: 0,
+            fast_forward: false,
+            rewind: RewindBuffer::new(DEFAULT_REWIND_SECONDS, TIME_PER_FRAME),
+            rewinding: false,
+            flight_model: FlightModel::Classic,
+            ships: ShipClass::builtin().to_vec(),
+            ship: 0,
+            wells: Vec::new(),
+            random_wells: 0,
+            stats: RunStats::default(),
+            lifetime: LifetimeStats::default(),
+            stats_path: None,
+            run_over: false,
+            high_scores: HighScores::default(),
+            high_scores_path: None,
+            player_name: String::new(),
+            achievements: AchievementTracker::default(),
+            events: EventQueue::new(),
+            subscribers: Vec::new(),
         }
     }
     pub fn get_comets_mut(&mut self) -> &mut Vec<Comet> { &mut self.comets }
     pub fn get_comets(&self) -> &Vec<Comet> { &self.comets }
     pub fn get_projectiles_mut(&mut self) -> &mut Vec<Projectile> { &mut self.projectiles }
     pub fn get_projectiles(&self) -> &Vec<Projectile> { &self.projectiles }
+    pub fn get_player(&self) -> &Player { &self.player }
+    pub fn get_phase(&self) -> GamePhase { self.game_state }
+    pub fn set_phase(&mut self, phase: GamePhase) {
+        if phase == self.game_state { return }
+        self.events.emit(GameEvent::PhaseChanged { from: self.game_state, to: phase });
+        self.game_state = phase;
+        // e.g. fire held through the restart of a run should not start shooting
+        self.input.debounce();
+    }
+    pub fn get_score(&self) -> i32 { self.scoring.get_score() }
+    pub fn get_lives(&self) -> u32 { self.player_lives }
+    pub fn get_stats(&self) -> &RunStats { &self.stats }
+    pub fn get_achievements(&self) -> &AchievementTracker { &self.achievements }
+    // lifetime stats including the current run once it is over
+    pub fn get_lifetime_stats(&self) -> LifetimeStats {
+        match self.run_over {
+            true => self.lifetime.with_run(&self.stats, self.scoring.get_score()),
+            false => self.lifetime.clone(),
+        }
+    }
+    // whether finished runs are kept on disk, headless games keep none
+    pub fn keeps_records(&self) -> bool { self.stats_path.is_some() }
+    // high scores including the current run once it is over
+    pub fn get_high_scores(&self) -> HighScores {
+        match self.run_over {
+            true => self.high_scores.with_run(self.high_score()),
+            false => self.high_scores.clone(),
+        }
+    }
+    fn high_score(&self) -> HighScore {
+        HighScore {
+            score: self.scoring.get_score(),
+            ship: self.get_ship().name.clone(),
+            time: self.stats.time,
+            name: self.player_name.clone(),
+        }
+    }
+    // where the current run made it into the high scores
+    pub fn get_high_score_rank(&self) -> Option<usize> { self.high_scores.rank_of(self.scoring.get_score()) }
+    pub fn get_arena(&self) -> Vec2 { self.arena }
+    pub fn get_view_size(&self) -> Vec2 { self.viewport.get_size() }
+    // in playfield coordinates
+    pub fn mouse_position(&self) -> Vec2 { self.viewport.mouse_position() }
+    pub fn get_custom_level(&self) -> Option<&Layout> { self.custom_level.as_ref() }
+    // played instead of the usual random start from the next reset on
+    pub fn set_custom_level(&mut self, layout: Option<Layout>) { self.custom_level = layout }
+    pub fn get_typed(&self) -> &[char] { &self.typed }
+    pub fn set_typing(&mut self, typing: bool) { self.typing = typing }
+    pub fn get_player_name(&self) -> &str { &self.player_name }
+    // a finished run that made the high scores is saved again under the new name
+    pub fn set_player_name(&mut self, name: String) {
+        self.player_name = name;
+        if !self.run_over { return }
+        if let Some(path) = self.high_scores_path.as_ref() {
+            if let Err(err) = self.get_high_scores().save(path) {
+                eprintln!("could not save high scores: {}", err);
+            }
+        }
+    }
+    pub fn get_input(&self) -> &InputState { &self.input }
+    pub fn get_gamepads(&self) -> &Gamepads { &self.gamepads }
+    pub fn is_debug_enabled(&self) -> bool { self.debug.is_enabled() }
+    pub fn toggle_debug(&mut self) { self.debug.toggle() }
+    pub fn get_game_duration(&self) -> f32 { self.game_duration }
+    pub fn get_time_per_tick(&self) -> f32 { self.time_per_tick }
+    pub fn get_time_scale(&self) -> f32 { self.time_scale }
+    pub fn set_time_scale(&mut self, scale: f32) { self.time_scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE) }
+    pub fn is_paused(&self) -> bool { self.paused }
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.pending_steps = 0;
+    }
+    // pauses if needed and runs exactly `ticks` physics ticks over the next frame
+    pub fn step(&mut self, ticks: u32) {
+        self.paused = true;
+        self.pending_steps += ticks;
+    }
+    // game time that passes during a frame of `delta_time` real seconds, to feed the physics accumulator;
+    // everything timed in the game advances per tick, so slow, fast and stepped time give the same results
+    pub fn scale_frame_time(&mut self, delta_time: f32) -> f32 {
+        if self.paused {
+            let steps = std::mem::take(&mut self.pending_steps);
+            return steps as f32 * self.time_per_tick;
+        }
+        match self.fast_forward {
+            true => delta_time * self.time_scale * FAST_FORWARD,
+            false => delta_time * self.time_scale,
+        }
+    }
+    pub fn get_scale_mode(&self) -> ScaleMode { self.viewport.get_mode() }
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) { self.viewport.set_mode(mode) }
+    // a world bigger than the playfield scrolls with the player and shows a radar
+    pub fn set_world_size(&mut self, size: Vec2) {
+        self.arena = size.max(PLAYFIELD);
+        self.scrolling = self.arena != PLAYFIELD;
+        self.camera = FollowCamera::new(self.arena / 2f32, PLAYFIELD);
+        self.spawn_player(self.arena / 2f32);
+    }
+    pub fn is_scrolling(&self) -> bool { self.scrolling }
+    pub fn set_tick_rate(&mut self, ticks_per_second: u32) {
+        self.time_per_tick = 1f32 / ticks_per_second.max(1) as f32;
+        self.rewind.set_duration(self.rewind.get_seconds(), self.time_per_tick);
+    }
+    // how far back holding R can go, memory grows with this and the tick rate
+    pub fn get_rewind_seconds(&self) -> f32 { self.rewind.get_seconds() }
+    pub fn set_rewind_seconds(&mut self, seconds: f32) { self.rewind.set_duration(seconds, self.time_per_tick) }
+    pub fn is_rewinding(&self) -> bool { self.rewinding }
+    pub fn get_flight_model(&self) -> FlightModel { self.flight_model }
+    pub fn get_ships(&self) -> &[ShipClass] { &self.ships }
+    pub fn get_ship(&self) -> &ShipClass { &self.ships[self.ship] }
+    pub fn get_ship_index(&self) -> usize { self.ship }
+    // takes effect with the next reset
+    pub fn set_ship_index(&mut self, idx: usize) { self.ship = idx % self.ships.len() }
+    // takes effect with the next reset
+    pub fn select_ship(&mut self, name: &str) -> Result<(), String> {
+        self.ship = self.ships.iter().position(|ship| ship.name == name).ok_or(format!("no ship called {}", name))?;
+        Ok(())
+    }
+    pub fn get_wells(&self) -> &[GravityWell] { &self.wells }
+    pub fn get_wells_mut(&mut self) -> &mut Vec<GravityWell> { &mut self.wells }
+    pub fn get_random_wells(&self) -> u32 { self.random_wells }
+    // takes effect with the next reset
+    pub fn set_random_wells(&mut self, count: u32) { self.random_wells = count }
+    fn spawn_player(&mut self, pos: Vec2) {
+        self.player = Player::at(pos);
+        self.player.set_ship(self.ships[self.ship].clone());
+        self.player.set_flight_model(self.flight_model);
+    }
+    pub fn set_flight_model(&mut self, model: FlightModel) {
+        self.flight_model = model;
+        self.player.set_flight_model(model);
+    }
+    // entities beyond the player, comets and projectiles live here and are driven by the systems
+    pub fn get_world(&self) -> &World { &self.world }
+    pub fn get_world_mut(&mut self) -> &mut World { &mut self.world }
+    // systems run in the order they were added, after the built-in ones
+    pub fn add_system(&mut self, system: Box<dyn System>) { self.systems.push(system) }
+    // subscribers hear about every event at the end of the tick it happened in
+    pub fn add_subscriber(&mut self, subscriber: Box<dyn Subscriber>) { self.subscribers.push(subscriber) }
 
-    pub fn update(&mut self) {
-        self.inputs();
-        match self.game_state {
-            PLAY => {
-                self.spawn_comet_with_spawn_rate();
-                self.player.update();
-                let mut new_comets: Vec<Comet> = Vec::new();
-                for comet in self.comets.iter_mut() {
-                    comet.update();
-                    if self.player.overlaps_shape(comet.get_shape()) {
-                        let direction = self.player.get_pos() - comet.get_pos();
-                        self.player.give_impulse(direction, COLLISION_KNOCK_BACK);
-                        if self.invincibility_timer == 0f32 {
-                            self.player_lives -= 1;
-                            self.invincibility_timer = INVINCIBILITY_DURATION;
-                            if self.player_lives <= 0 { self.game_state = END }
-                        }
-                    }
-                }
-                for comet in self.comets.iter_mut() {
-                    for projectile in self.projectiles.iter_mut() {
-                        if comet.contains(projectile.get_tip_pos()) {
-                            match comet.get_size() {
-                                Size::Three => {
-                                    for _idx in 0..SPLIT_RATIO {
-                                        new_comets.push(Comet::spawn(Size::Two, Some(comet.get_pos())));
-                                    }
-                                }
-                                Size::Two => {
-                                    for _idx in 0..SPLIT_RATIO {
-                                        new_comets.push(Comet::spawn(Size::One, Some(comet.get_pos())));
-                                    }
-                                }
-                                Size::One => ()
-                            }
-                            comet.destroy();
-                            self.score += 10;
-                            projectile.destroy();
-                            break;
-                        }
-                    }
-                }
-                self.comets.append(&mut new_comets);
-                for projectile in self.projectiles.iter_mut() {
-                    projectile.update();
-                    if projectile.is_off_screen() {
-                        projectile.destroy();
-                        self.score -= 1;
-                    }
+    // a loaded level script takes over comet spawning from spawn_comet_with_spawn_rate
+    pub fn load_script(&mut self, path: &str) -> Result<(), String> {
+        self.script = Some(LevelScript::load(path)?);
+        Ok(())
+    }
+    fn script_view(&self) -> ScriptView {
+        ScriptView {
+            score: self.scoring.get_score(),
+            lives: self.player_lives,
+            game_duration: self.game_duration,
+            comet_count: self.comets.len(),
+            arena: self.arena,
+        }
+    }
+    fn apply_script_commands(&mut self, commands: Vec<ScriptCommand>) {
+        for command in commands {
+            match command {
+                ScriptCommand::SpawnComet { size, pos, vel } => {
+                    let pos = pos.or_else(|| self.spawn_position());
+                    let mut comet = Comet::spawn(size, pos, self.arena, &mut self.rng);
+                    if let Some(vel) = vel { comet.set_vel(vel) }
+                    self.comets.push(comet);
                 }
-                self.projectiles.retain(|projectile| projectile.is_alive());
-                self.comets.retain(|comet| comet.is_alive());
+                ScriptCommand::Message { text, seconds } => self.messages.push((text, seconds)),
+                ScriptCommand::SpawnWell { pos, strength, horizon } => self.wells.push(GravityWell::new(pos, strength, horizon.max(1f32))),
+                ScriptCommand::ClearWells => self.wells.clear(),
             }
-            _ => ()
         }
+    }
 
+    // one physics tick, the inputs come from whatever is on screen and are read after the snapshot for drawing
+    pub fn update(&mut self, inputs: impl FnOnce(&mut GameState)) {
+        if let Some(script) = self.script.as_mut() {
+            script.reload_if_changed();
+        }
+        self.rewinding = matches!(self.game_state, PLAY | END) && !self.console.is_open() && !self.typing && is_key_down(R);
+        if self.rewinding {
+            self.step_back();
+            return;
+        }
+        if self.game_state == PLAY {
+            let snapshot = self.snapshot();
+            self.rewind.push(snapshot);
+        }
+        self.store_previous();
+        if !self.console.is_open() {
+            inputs(self);
+        }
+        self.refresh_all_cool_downs(self.time_per_tick);
+        let start = Instant::now();
+        self.tick();
+        self.debug.record_tick_time(start.elapsed().as_secs_f32());
     }
-    pub fn draw(&self) {
-        clear_background(BLACK);
-        match self.game_state {
-            PLAY => {
-                match self.invincibility_timer {
-                    0f32 => self.player.draw(WHITE),
-                    _ => self.player.draw(GRAY),
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            player: self.player.clone(),
+            comets: self.comets.clone(),
+            projectiles: self.projectiles.clone(),
+            world: self.world.clone(),
+            wells: self.wells.clone(),
+            camera: self.camera.clone(),
+            rng: self.rng.clone(),
+            scoring: self.scoring.clone(),
+            stats: self.stats.clone(),
+            achievements: self.achievements.get_run_progress().clone(),
+            player_lives: self.player_lives,
+            weapon_cd: self.weapon_cd,
+            invincibility_timer: self.invincibility_timer,
+            comet_spawn_timer: self.comet_spawn_timer,
+            game_duration: self.game_duration,
+            messages: self.messages.clone(),
+        }
+    }
+    // undoes the last tick, a game over is undone as well
+    fn step_back(&mut self) {
+        let Some(snapshot) = self.rewind.pop() else { return };
+        self.player = snapshot.player;
+        self.comets = snapshot.comets;
+        self.projectiles = snapshot.projectiles;
+        self.world = snapshot.world;
+        self.wells = snapshot.wells;
+        self.camera = snapshot.camera;
+        self.rng = snapshot.rng;
+        self.scoring = snapshot.scoring;
+        self.stats = snapshot.stats;
+        self.achievements.set_run_progress(snapshot.achievements);
+        self.player_lives = snapshot.player_lives;
+        self.weapon_cd = snapshot.weapon_cd;
+        self.invincibility_timer = snapshot.invincibility_timer;
+        self.comet_spawn_timer = snapshot.comet_spawn_timer;
+        self.game_duration = snapshot.game_duration;
+        self.messages = snapshot.messages;
+        self.set_phase(PLAY);
+        self.run_over = false;
+        // objects come and go between snapshots, so rewinding is drawn tick by tick without interpolation
+        self.store_previous();
+    }
+    // every moving thing keeps a copy of its state from before the tick (prev_pos and the like), taken here before
+    // the inputs; frames fall between ticks, so drawing blends from that copy at alpha 0 to the current state at alpha 1
+    fn store_previous(&mut self) {
+        self.player.store_previous();
+        self.camera.store_previous();
+        for comet in self.comets.iter_mut() {
+            comet.store_previous();
+        }
+        for projectile in self.projectiles.iter_mut() {
+            projectile.store_previous();
+        }
+        store_previous(&mut self.world);
+    }
+    // inputs that need every rendered frame instead of every physics tick,
+    // true while the console has the keyboard and nothing else should read it
+    pub fn frame_inputs(&mut self, delta_time: f32) -> bool {
+        self.debug.record_frame_time(delta_time);
+        self.debug.age(delta_time);
+        if is_key_pressed(GraveAccent) {
+            self.console.toggle();
+        }
+        // typed characters queue up until read, so they have to be drained even while the console is closed
+        let mut typed: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        typed.reverse();
+        self.typed.clear();
+        // keys typed into the console do not fly the ship
+        let (console, mouse_fires) = (self.console.is_open(), self.flight_model == FlightModel::TwinStick);
+        self.gamepads.poll();
+        let gamepads = &self.gamepads;
+        self.input.sample(|action| !console && action.is_down(mouse_fires, gamepads));
+        if self.console.is_open() {
+            if let Some(line) = self.console.inputs(&typed) {
+                let result = self.execute(&line);
+                for line in result.unwrap_or_else(|err| err).lines() {
+                    self.console.print(line);
                 }
-
-                for comet in self.comets.iter() {
-                    comet.draw();
+            }
+            return true;
+        }
+        if is_key_pressed(F3) {
+            self.debug.toggle();
+        }
+        self.typed = typed;
+        false
+    }
+    // advances the simulation by one physics step without reading the keyboard
+    pub fn tick(&mut self) {
+        if self.game_state == PLAY {
+            let view = self.script_view();
+            match self.script.as_mut() {
+                Some(script) => {
+                    let commands = script.tick(view);
+                    self.apply_script_commands(commands);
                 }
-                for projectile in self.projectiles.iter() {
-                    projectile.draw();
+                None => self.spawn_comet_with_spawn_rate(),
+            }
+            self.apply_gravity();
+            self.player.update(self.arena, self.time_per_tick);
+            self.events.emit(GameEvent::Tick {
+                delta_time: self.time_per_tick,
+                distance: self.player.get_vel().length() * self.time_per_tick,
+            });
+            if self.wells.iter().any(|well| well.swallows(self.player.get_pos(), self.arena)) {
+                self.swallow_player();
+            }
+            if self.scrolling {
+                self.camera.update(self.player.get_pos(), self.player.get_vel(), self.player.get_dir(), self.arena, self.time_per_tick);
+            }
+            for comet in self.comets.iter_mut() {
+                comet.update(self.arena, self.time_per_tick);
+                if self.wells.iter().any(|well| well.swallows(comet.get_pos(), self.arena)) {
+                    self.debug.log(format!("{:?} comet swallowed at {:.0} {:.0}", comet.get_size(), comet.get_pos().x, comet.get_pos().y));
+                    comet.destroy();
+                    self.events.emit(GameEvent::CometDestroyed {
+                        size: comet.get_size(),
+                        sides: comet.get_shape().get_sides(),
+                        pos: comet.get_pos(),
+                        by: Cause::Well,
+                    });
                 }
-                // draw score
-                draw_text_ex(&format!("Score: {}", self.score), 200f32, 40f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size: 30,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-                draw_text_ex(&format!("Lives: {}", self.player_lives), 20f32, 40f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size: 30,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-            }
-            END => {
-                let text = "GAME OVER!";
-                let (font_size) = (50);
-                let text_dim = measure_text(text, Option::from(&self.font), font_size, 1f32);
-                draw_text_ex(text, screen_width()/2f32 - text_dim.width/2f32, 200f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-                let text = &format!("YOUR SCORE WAS: {}", self.score);
-                let text_dim = measure_text(text, Option::from(&self.font), font_size, 1f32);
-                draw_text_ex(text, screen_width()/2f32 - text_dim.width/2f32, 280f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-                let text = "PRESS ENTER TO RESTART";
-                let text_dim = measure_text(text, Option::from(&self.font), font_size, 1f32);
-                draw_text_ex(text, screen_width()/2f32 - text_dim.width/2f32, 360f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-            }
-            MENU => {
-                let text = "ASTEROIDS";
-                let font_size = 50;
-                let text_dim = measure_text(text, Option::from(&self.font), font_size+40, 1f32);
-                draw_text_ex(text, screen_width()/2f32 - text_dim.width/2f32, 200f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size: font_size+40,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-                let text = "PRESS ENTER TO START";
-                let text_dim = measure_text(text, Option::from(&self.font), font_size, 1f32);
-                draw_text_ex(text, screen_width()/2f32 - text_dim.width/2f32, 280f32, TextParams {
-                    font: Option::from(&self.font),
-                    font_size,
-                    font_scale: 1f32,
-                    font_scale_aspect: 1f32,
-                    rotation: 0.0,
-                    color: WHITE,
-                });
-
             }
-            _ => ()
+            for projectile in self.projectiles.iter_mut() {
+                projectile.update(self.time_per_tick);
+                if self.wells.iter().any(|well| well.swallows(projectile.get_pos(), self.arena)) {
+                    projectile.destroy();
+                    continue;
+                }
+                let missed = match self.scrolling {
+                    true => !self.camera.can_see(projectile.get_pos(), 100f32, self.arena) || is_outside(projectile.get_pos(), self.arena, 100f32),
+                    false => projectile.is_off_screen(self.arena),
+                };
+                if missed {
+                    projectile.destroy();
+                    self.events.emit(GameEvent::ProjectileMissed { pos: projectile.get_pos() });
+                }
+            }
+            self.run_systems();
+            self.projectiles.retain(|projectile| projectile.is_alive());
+            let had_comets = !self.comets.is_empty();
+            self.comets.retain(|comet| comet.is_alive());
+            // clearing the field ends a wave, scripted or not
+            if had_comets && self.comets.is_empty() {
+                self.events.emit(GameEvent::WaveCleared);
+            }
+        }
+        self.dispatch_events();
+    }
+    // runs the entity systems over debris, effects and the mirrored player, comets and projectiles,
+    // then carries out what the collisions did to the latter
+    fn run_systems(&mut self) {
+        self.sync_bodies();
+        let mut ctx = SystemContext::new(self.arena, self.time_per_tick);
+        for system in self.systems.iter_mut() {
+            system.run(&mut self.world, &mut ctx);
+        }
+        let mut new_comets: Vec<Comet> = Vec::new();
+        for destroyed in ctx.destroyed.iter() {
+            match destroyed.body {
+                Some(Body::Comet(idx)) => {
+                    // the last contact is the one that took the last hit point
+                    let killer = ctx.contacts.iter().rev().find_map(|contact| contact.other(Body::Comet(idx)));
+                    let comet = &mut self.comets[idx];
+                    let (impact, dir, by) = match killer {
+                        Some((Some(Body::Projectile(projectile)), tip)) => (tip, self.projectiles[projectile].get_dir(), Cause::Projectile),
+                        Some((_, pos)) => (pos, (comet.get_pos() - pos).try_normalize().unwrap_or(Vec2::X), Cause::Entity),
+                        None => (comet.get_pos(), Vec2::X, Cause::Entity),
+                    };
+                    let (mut pieces, dust) = comet.fracture(impact, dir, &mut self.rng);
+                    new_comets.append(&mut pieces);
+                    dust.iter().for_each(|piece| piece.crumble(&mut self.world, &mut self.rng));
+                    self.debug.log(format!("{:?} comet hit at {:.0} {:.0}", comet.get_size(), impact.x, impact.y));
+                    comet.destroy();
+                    self.events.emit(GameEvent::CometDestroyed {
+                        size: comet.get_size(),
+                        sides: comet.get_shape().get_sides(),
+                        pos: impact,
+                        by,
+                    });
+                }
+                Some(Body::Projectile(idx)) => self.projectiles[idx].destroy(),
+                // the player has lives instead of health
+                Some(Body::Player) => (),
+                None => self.events.emit(GameEvent::EntityDestroyed { pos: destroyed.pos, points: destroyed.points }),
+            }
+        }
+        self.comets.append(&mut new_comets);
+        for contact in ctx.contacts.iter() {
+            match contact.other(Body::Player) {
+                Some((Some(Body::Comet(_)), from)) => self.hit_player(from, Cause::Comet),
+                Some((_, from)) => self.hit_player(from, Cause::Entity),
+                None => (),
+            }
+        }
+    }
+    // debris and effects, without the copies of the player, comets and projectiles
+    fn count_entities(&self) -> usize {
+        self.world.len() - self.world.bodies.iter().count()
+    }
+    // puts this tick's player, comets and projectiles into the world in place of last tick's copies
+    fn sync_bodies(&mut self) {
+        let stale: Vec<Entity> = self.world.bodies.iter().map(|(entity, _)| entity).collect();
+        for entity in stale {
+            self.world.despawn(entity);
+        }
+        let player = Transform::new(self.player.get_pos(), Vec2::X.angle_between(self.player.get_dir()));
+        let collider = Collider { hitbox: self.player.get_hitbox(), damage: 0 };
+        self.world.spawn_body(Body::Player, player, collider, Team::Player, None);
+        // comets pay out through CometDestroyed, which knows about sizes and combos
+        let health = Some(Health { hp: 1, points: 0 });
+        for (idx, comet) in self.comets.iter().enumerate().filter(|(_, comet)| comet.is_alive()) {
+            let shape = comet.get_shape();
+            let collider = Collider { hitbox: Hitbox::Polygon { vertices: shape.get_vertices().to_vec() }, damage: 1 };
+            self.world.spawn_body(Body::Comet(idx), Transform::new(shape.get_pos(), shape.get_rot()), collider, Team::Hostile, health);
+        }
+        for (idx, projectile) in self.projectiles.iter().enumerate().filter(|(_, projectile)| projectile.is_alive()) {
+            let collider = Collider { hitbox: Hitbox::Point, damage: 1 };
+            self.world.spawn_body(Body::Projectile(idx), Transform::new(projectile.get_tip_pos(), 0f32), collider, Team::Player, health);
+        }
+    }
+    fn apply_gravity(&mut self) {
+        if self.wells.is_empty() { return }
+        let (wells, arena, delta_time) = (&self.wells, self.arena, self.time_per_tick);
+        self.player.apply_acceleration(total_acceleration(wells, self.player.get_pos(), arena), delta_time);
+        for comet in self.comets.iter_mut() {
+            let vel = comet.get_vel() + total_acceleration(wells, comet.get_pos(), arena) * delta_time;
+            comet.set_vel(vel);
+        }
+        for projectile in self.projectiles.iter_mut() {
+            projectile.accelerate(total_acceleration(wells, projectile.get_pos(), arena), delta_time);
+        }
+    }
+    // crossing an event horizon always costs a life, invincible or not, and puts the ship back at its spawn
+    fn swallow_player(&mut self) {
+        self.debug.log(format!("player swallowed at {:.0} {:.0}", self.player.get_pos().x, self.player.get_pos().y));
+        if !self.god_mode {
+            self.player_lives = self.player_lives.saturating_sub(1);
+            if self.player_lives == 0 { self.set_phase(END) }
+        }
+        self.events.emit(GameEvent::PlayerHit { from: self.player.get_pos(), by: Cause::Well, life_lost: !self.god_mode });
+        let spawn = self.player_spawn();
+        self.spawn_player(spawn);
+        self.camera.snap_to(spawn);
+        self.invincibility_timer = INVINCIBILITY_DURATION;
+    }
+    fn player_spawn(&self) -> Vec2 {
+        match self.custom_level.as_ref() {
+            Some(layout) => layout.player_spawn,
+            None => self.arena / 2f32,
+        }
+    }
+    // hands the events of the tick to the subscribers, then applies what they worked out
+    fn dispatch_events(&mut self) {
+        let events = self.events.drain();
+        for event in events.iter() {
+            self.scoring.handle(event);
+            self.stats.handle(event);
+            self.achievements.handle(event);
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.handle(event);
+            }
+        }
+        let extra_lives = self.scoring.take_extra_lives();
+        if extra_lives > 0 {
+            self.player_lives += extra_lives;
+            self.scoring.popup("EXTRA LIFE".to_string(), self.player.get_pos(), YELLOW);
+        }
+        if let Some((accuracy, bonus)) = self.scoring.take_wave_bonus() {
+            self.debug.log(format!("wave cleared, accuracy {}%, bonus {}", accuracy, bonus));
+            self.messages.push((format!("ACCURACY {}%  +{}", accuracy, bonus), 2f32));
+        }
+        if events.iter().any(|event| matches!(event, GameEvent::PhaseChanged { to: END, .. })) {
+            self.save_run();
+        }
+    }
+    // saves the lifetime stats, high scores and achievements with this run added; the run only joins them for good
+    // once the next one starts, so rewinding out of a game over and dying again does not count it twice
+    fn save_run(&mut self) {
+        self.run_over = true;
+        if let Err(err) = self.achievements.save() {
+            eprintln!("could not save achievements: {}", err);
+        }
+        if let Some(path) = self.stats_path.as_ref() {
+            if let Err(err) = self.get_lifetime_stats().save(path) {
+                eprintln!("could not save lifetime stats: {}", err);
+            }
+        }
+        if let Some(path) = self.high_scores_path.as_ref() {
+            if let Err(err) = self.get_high_scores().save(path) {
+                eprintln!("could not save high scores: {}", err);
+            }
         }
+    }
+    fn hit_player(&mut self, from: Vec2, by: Cause) {
+        let direction = self.player.get_pos() - from;
+        self.player.give_impulse(direction, COLLISION_KNOCK_BACK * self.time_per_tick);
+        self.debug.log(format!("player hit from {:.0} {:.0}{}", from.x, from.y,
+                               if self.invincibility_timer == 0f32 && !self.god_mode { ", life lost" } else { ", invincible" }));
+        let life_lost = self.invincibility_timer == 0f32 && !self.god_mode;
+        if life_lost {
+            self.player_lives -= 1;
+            self.invincibility_timer = INVINCIBILITY_DURATION;
+            if self.player_lives == 0 { self.set_phase(END) }
+        }
+        self.events.emit(GameEvent::PlayerHit { from, by, life_lost });
+    }
+    // sets up the playfield for the scenes to draw on and puts the console on top
+    pub fn draw(&self, scenes: impl FnOnce(&Font)) {
+        let Some(font) = self.font.as_ref() else { return };
+        let view = self.viewport.get_size();
+        self.viewport.begin();
+        scenes(font);
+        self.viewport.look_at(view / 2f32);
+        self.console.draw(&mut Ui::new(font, view, 0));
+        self.viewport.end();
+    }
+    // the world and the HUD, alpha is how far the time is between the last tick and the next one, 0 to 1
+    pub fn draw_play(&self, font: &Font, alpha: f32) {
+        let view = self.viewport.get_size();
+        let targets = match self.scrolling {
+            true => self.camera.draw_targets(self.arena, alpha),
+            false => vec![view / 2f32],
+        };
+        for target in targets {
+            self.viewport.look_at(target);
+            match self.invincibility_timer {
+                0f32 => self.player.draw(WHITE, alpha),
+                _ => self.player.draw(GRAY, alpha),
+            }
 
+            for comet in self.comets.iter() {
+                comet.draw(alpha);
+            }
+            for projectile in self.projectiles.iter() {
+                projectile.draw(alpha);
+            }
+            draw_world(&self.world, alpha);
+            self.scoring.draw_popups(&mut Ui::new(font, view, 0));
+            for well in self.wells.iter() {
+                well.draw();
+            }
+            if self.debug.is_enabled() {
+                self.debug.draw_hitboxes(&self.player, &self.comets, &self.projectiles);
+            }
+        }
+        self.viewport.look_at(view / 2f32);
+        if self.scrolling {
+            self.draw_radar(alpha);
+        }
+        if self.debug.is_enabled() {
+            let spawning = match self.script {
+                Some(_) => "spawning by script".to_string(),
+                None => format!("spawn timer {:.1} / {:.1} s", self.comet_spawn_timer, self.comet_spawn_threshold()),
+            };
+            self.debug.draw_stats(&mut Ui::new(font, view, 0), &[
+                format!("comets {}   projectiles {}   entities {}", self.comets.len(), self.projectiles.len(), self.count_entities()),
+                spawning,
+            ]);
+        }
+        let mut ui = Ui::new(font, view, 0);
+        ui.anchored(Anchor::TopLeft, Vec2::new(20f32, 15f32), 4f32, |ui| {
+            ui.row(40f32, |ui| {
+                ui.label(&format!("Lives: {}", self.player_lives));
+                ui.label(&format!("Score: {}", self.scoring.get_score()));
+            });
+            if let Some(text) = self.scoring.combo_label() {
+                ui.set_style(20, YELLOW);
+                ui.label(&text);
+            }
+        });
+        // time controls on top, script messages below with the newest at the bottom
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 15f32), 10f32, |ui| {
+            ui.set_style(DEFAULT_SIZE, YELLOW);
+            match self.time_label() {
+                Some(text) => ui.label(&text),
+                None => ui.space(DEFAULT_SIZE as f32),
+            }
+            ui.space(20f32);
+            ui.set_style(DEFAULT_SIZE, WHITE);
+            for (text, _) in self.messages.iter() {
+                ui.label(text);
+            }
+        });
+        self.achievements.draw_toasts(font, view);
     }
     pub fn shoot(&mut self) {
         if self.weapon_cd == 0f32 {
-            self.projectiles.push(Projectile::new(PROJECTILE_SPEED, self.player.get_dir(), self.player.get_pos()));
-            self.weapon_cd = SHOOTING_COOLDOWN;
+            let ship = self.player.get_ship();
+            for gun in ship.guns.iter() {
+                let dir = Vec2::from_angle(gun.angle).rotate(self.player.get_dir());
+                let pos = self.player.to_world(gun.offset);
+                self.projectiles.push(Projectile::new(ship.projectile_speed, dir, pos));
+                self.events.emit(GameEvent::ProjectileFired { pos, dir });
+            }
+            self.weapon_cd = ship.cooldown;
         }
     }
     pub fn refresh_all_cool_downs(&mut self, delta_time: f32) {
@@ -225,79 +796,270 @@ impl GameState {
             _ if self.weapon_cd <= delta_time => self.weapon_cd = 0f32,
             _ => self.weapon_cd -= delta_time,
         }
-        match self.game_state {
-            PLAY => {
-                match self.invincibility_timer {
-                    _ if self.invincibility_timer <= delta_time => self.invincibility_timer = 0f32,
-                    _ => self.invincibility_timer -= delta_time,
-                }
-                self.game_duration += delta_time;
-                self.comet_spawn_timer += delta_time;
+        if self.game_state == PLAY {
+            match self.invincibility_timer {
+                _ if self.invincibility_timer <= delta_time => self.invincibility_timer = 0f32,
+                _ => self.invincibility_timer -= delta_time,
+            }
+            self.game_duration += delta_time;
+            self.comet_spawn_timer += delta_time;
+            for (_, remaining) in self.messages.iter_mut() {
+                *remaining -= delta_time;
+            }
+            self.messages.retain(|(_, remaining)| *remaining > 0f32);
+            self.scoring.update(delta_time);
+            self.achievements.update(delta_time);
+            if let Some(script) = self.script.as_mut() {
+                script.advance_timers(delta_time);
             }
-            _ => ()
         }
     }
     pub fn spawn_comet(&mut self) {
-        self.comets.push(Comet::spawn(Size::Three, None));
+        let pos = self.spawn_position();
+        self.comets.push(Comet::spawn(Size::Three, pos, self.arena, &mut self.rng));
+    }
+    // somewhere the camera does not see when scrolling, otherwise None for the usual spawn at the border
+    fn spawn_position(&mut self) -> Option<Vec2> {
+        if !self.scrolling { return None }
+        let mut pos = Vec2::ZERO;
+        for _attempt in 0..32 {
+            pos = Vec2::new(self.rng.gen_range(0f32..self.arena.x), self.rng.gen_range(0f32..self.arena.y));
+            if !self.camera.can_see(pos, OFF_CAMERA_SPAWN_MARGIN, self.arena) { break }
+        }
+        Some(pos)
+    }
+    // shown on the HUD whenever time does not run normally
+    fn time_label(&self) -> Option<String> {
+        match self.paused {
+            _ if self.rewinding => Some(format!("<< REWIND {:.1} s", self.rewind.len() as f32 * self.time_per_tick)),
+            true => Some("PAUSED  (N: STEP)".to_string()),
+            false if self.fast_forward => Some(format!(">> x{}", self.time_scale * FAST_FORWARD)),
+            false if self.time_scale != 1f32 => Some(format!("x{}", self.time_scale)),
+            false => None,
+        }
+    }
+    // minimap of the whole world in the top right corner
+    fn draw_radar(&self, alpha: f32) {
+        let scale = RADAR_WIDTH / self.arena.x;
+        let origin = Vec2::new(self.viewport.get_size().x - RADAR_WIDTH - 20f32, 20f32);
+        let radar = |pos: Vec2| origin + (pos - self.arena * (pos / self.arena).floor()) * scale;
+        let frame = Color::new(0.5f32, 0.5f32, 0.5f32, 0.8f32);
+        draw_rectangle_lines(origin.x, origin.y, RADAR_WIDTH, self.arena.y * scale, 1f32, frame);
+        for comet in self.comets.iter() {
+            let pos = radar(comet.get_pos());
+            draw_circle(pos.x, pos.y, (comet.get_shape().get_rad() * scale).max(1.5f32), WHITE);
+        }
+        let view = self.camera.get_view() * scale;
+        let corner = radar(self.camera.get_pos(alpha)) - view / 2f32;
+        draw_rectangle_lines(corner.x, corner.y, view.x, view.y, 1f32, frame);
+        for well in self.wells.iter() {
+            let pos = radar(well.pos);
+            draw_circle(pos.x, pos.y, 3f32, PURPLE);
+        }
+        let pos = radar(self.player.get_pos());
+        draw_circle(pos.x, pos.y, 2.5f32, YELLOW);
+    }
+    // seconds between comets, shrinking the longer the game goes
+    fn comet_spawn_threshold(&self) -> f32 {
+        BASE_COMET_SPAWN_RATE / (0.5f32 * self.game_duration.sqrt())
     }
     pub fn spawn_comet_with_spawn_rate(&mut self) {
-        if self.comet_spawn_timer >= BASE_COMET_SPAWN_RATE / (0.5f32 * self.game_duration.sqrt()) {
+        if self.comet_spawn_timer >= self.comet_spawn_threshold() {
             self.comet_spawn_timer = 0f32;
             self.spawn_comet();
         }
     }
     pub fn accelerate(&mut self, factor: f32) {
-        self.player.accelerate(factor);
+        self.player.accelerate(factor, self.time_per_tick);
     }
-    pub fn rotate(&mut self, right: bool) {
-        if right { self.player.rotate(ROTATION_SPEED) } else { self.player.rotate(-ROTATION_SPEED) }
+    // -1 turns left, 1 turns right, how fast depends on the flight model
+    pub fn turn(&mut self, input: f32) {
+        self.player.turn(input, self.time_per_tick);
+    }
+    // twin-stick aims at the mouse, the shortest way around when the world wraps
+    fn mouse_aim(&self) -> Vec2 {
+        let mouse = self.viewport.mouse_position();
+        let target = match self.scrolling {
+            true => self.camera.get_pos(1f32) - self.viewport.get_size() / 2f32 + mouse,
+            false => mouse,
+        };
+        toroidal_offset(target - self.player.get_pos(), self.arena)
+    }
+    // single steps, slow motion and fast-forward, read every frame so they work while paused
+    pub fn time_inputs(&mut self) {
+        self.fast_forward = self.game_state == PLAY && is_key_down(F);
+        if self.game_state != PLAY { return }
+        if is_key_pressed(N) && self.paused {
+            self.step(1);
+        }
+        if is_key_pressed(Minus) {
+            self.set_time_scale(self.time_scale / 2f32);
+        }
+        if is_key_pressed(Equal) {
+            self.set_time_scale(self.time_scale * 2f32);
+        }
+        if is_key_pressed(Key0) {
+            self.set_time_scale(1f32);
+        }
     }
-    pub fn inputs(&mut self) {
+    // flying and shooting, read once per tick from the input sampled by the last frame
+    pub fn play_inputs(&mut self) {
+        let held = |action: Action| self.input.held(action);
         match self.game_state {
+            PLAY if self.flight_model == FlightModel::TwinStick => {
+                let axis = |negative: Action, positive: Action| held(positive) as i32 as f32 - held(negative) as i32 as f32;
+                let direction = Vec2::new(axis(Action::Left, Action::Right), axis(Action::Thrust, Action::Brake));
+                self.player.steer(direction, self.time_per_tick);
+                self.player.aim(self.mouse_aim());
+            }
             PLAY => {
-                if is_key_down(Left) || is_key_down(A) {
-                    self.rotate(false);
+                let (left, right, thrust, brake) = (held(Action::Left), held(Action::Right), held(Action::Thrust), held(Action::Brake));
+                if left {
+                    self.turn(-1f32);
                 }
-                if is_key_down(Right) || is_key_down(D) {
-                    self.rotate(true);
+                if right {
+                    self.turn(1f32);
                 }
-                if is_key_down(Up) || is_key_down(W) {
+                if thrust {
                     self.accelerate(1f32);
                 }
-                if is_key_down(Down) || is_key_down(S) {
+                if brake {
                     self.accelerate(-0.5f32);
                 }
-                if is_key_down(Space) {
-                    self.shoot();
-                }
-                if is_key_down(C) {
-                    self.spawn_comet()
-                }
             }
-            END => {
-                if is_key_down(Enter) {
-                    self.game_state = PLAY;
-                    self.reset();
-                }
+            MENU | END => return,
+        }
+        if self.input.wants_fire() && self.weapon_cd == 0f32 {
+            self.shoot();
+            self.input.take_fire();
+        }
+        self.input.age(self.time_per_tick);
+    }
+
+    // runs one console line, the text is what the console prints back
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        match Command::parse(line)? {
+            Command::SpawnComet { size, pos } => {
+                let pos = pos.or_else(|| self.spawn_position());
+                let text = format!("spawned {:?} comet", size);
+                self.comets.push(Comet::spawn(size, pos, self.arena, &mut self.rng));
+                Ok(text)
             }
-            MENU => {
-                if is_key_down(Enter) {
-                    self.game_state = PLAY;
-                    self.reset();
-                }
+            Command::God => {
+                self.god_mode = !self.god_mode;
+                Ok(format!("god mode {}", if self.god_mode { "on" } else { "off" }))
             }
-            _ => ()
+            Command::Lives(lives) => {
+                self.player_lives = lives;
+                if lives == 0 && self.game_state == PLAY { self.set_phase(END) }
+                Ok(format!("lives set to {}", lives))
+            }
+            Command::Score(score) => {
+                self.scoring.set_score(score);
+                Ok(format!("score set to {}", score))
+            }
+            Command::TimeScale(scale) => self.execute(&format!("set time_scale {}", scale)),
+            Command::Pause => {
+                self.set_paused(!self.paused);
+                Ok(String::from(if self.paused { "paused" } else { "resumed" }))
+            }
+            Command::Step(ticks) => {
+                self.step(ticks);
+                Ok(format!("stepping {} ticks", ticks))
+            }
+            Command::Seed(seed) => {
+                self.reseed(seed);
+                Ok(format!("reseeded with {}", seed))
+            }
+            Command::Clear => {
+                let count = self.comets.len() + self.projectiles.len() + self.count_entities();
+                self.comets.clear();
+                self.projectiles.clear();
+                self.world.clear();
+                Ok(format!("removed {} objects", count))
+            }
+            Command::Set { key, value } => self.set(&key, &value),
+            Command::Help => Ok(Command::help().join("\n")),
+        }
+    }
+    fn set(&mut self, key: &str, value: &str) -> Result<String, String> {
+        let invalid = || format!("invalid value {} for {}", value, key);
+        match key {
+            "tick_rate" => self.set_tick_rate(value.parse().map_err(|_| invalid())?),
+            "scale_mode" => self.set_scale_mode(ScaleMode::from_name(value).ok_or_else(invalid)?),
+            "world_size" => {
+                let (w, h) = value.split_once('x').ok_or_else(invalid)?;
+                self.set_world_size(Vec2::new(w.parse().map_err(|_| invalid())?, h.parse().map_err(|_| invalid())?));
+            }
+            "debug" => match value {
+                "on" if !self.debug.is_enabled() => self.debug.toggle(),
+                "off" if self.debug.is_enabled() => self.debug.toggle(),
+                "on" | "off" => (),
+                _ => return Err(invalid()),
+            },
+            "ship" => self.select_ship(value)?,
+            "wells" => self.set_random_wells(value.parse().map_err(|_| invalid())?),
+            "flight_model" => self.set_flight_model(FlightModel::from_name(value).ok_or_else(invalid)?),
+            "rewind_seconds" => self.set_rewind_seconds(value.parse().map_err(|_| invalid())?),
+            "time_scale" => {
+                self.set_time_scale(value.parse().map_err(|_| invalid())?);
+                return Ok(format!("time_scale = {}", self.time_scale));
+            }
+            _ => return Err(format!("unknown setting {}, try one of: {}", key, SETTINGS.join(" "))),
         }
+        Ok(format!("{} = {}", key, value))
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    // a fresh run with the selected ship, on the custom level if there is one
+    pub fn start_run(&mut self) {
+        self.set_phase(PLAY);
+        self.reset();
     }
 
     pub fn reset(&mut self) {
-        self.player = Player::new();
         self.comets.clear();
+        self.world.clear();
+        self.wells.clear();
+        match self.custom_level.as_ref() {
+            Some(layout) => {
+                self.comets.extend(layout.comets.iter().map(|comet| comet.to_comet()));
+                self.wells.extend(layout.wells.iter().cloned());
+            }
+            None => for _idx in 0..self.random_wells {
+                let well = GravityWell::random(self.arena, self.arena / 2f32, &mut self.rng);
+                self.wells.push(well);
+            },
+        }
+        let spawn = self.player_spawn();
+        self.spawn_player(spawn);
+        self.camera.snap_to(self.player.get_pos());
         self.projectiles.clear();
-        self.score = 0;
+        if self.run_over {
+            self.lifetime = self.get_lifetime_stats();
+            self.high_scores = self.get_high_scores();
+            self.achievements.commit_run();
+            self.run_over = false;
+        }
+        self.scoring = Scoring::new();
+        self.stats = RunStats::default();
+        self.events.emit(GameEvent::RunStarted);
         self.weapon_cd = 0f32;
-        self.player_lives = 3;
+        self.player_lives = self.player.get_ship().lives;
         self.game_duration = 0f32;
-        self.spawn_comet();
+        self.messages.clear();
+        self.rewind.clear();
+        let view = self.script_view();
+        match self.script.as_mut() {
+            Some(script) => {
+                let commands = script.start(view);
+                self.apply_script_commands(commands);
+            }
+            None if self.custom_level.is_none() => self.spawn_comet(),
+            None => (),
+        }
     }
 }
\ No newline at end of file
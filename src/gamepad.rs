@@ -0,0 +1,88 @@
+#[cfg(feature = "gamepad")]
+use gilrs::{Axis, Button, Gilrs};
+
+#[cfg(feature = "gamepad")]
+const STICK_DEADZONE: f32 = 0.5f32;     // how far the left stick has to lean to count as a direction
+
+// the buttons the game reads, the left stick counts as the d-pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    South,
+    East,
+    West,
+    LeftTrigger,
+    RightTrigger,
+    Start,
+}
+
+#[cfg(feature = "gamepad")]
+const BUTTONS: [(Button, PadButton); 10] = [
+    (Button::DPadUp, PadButton::Up),
+    (Button::DPadDown, PadButton::Down),
+    (Button::DPadLeft, PadButton::Left),
+    (Button::DPadRight, PadButton::Right),
+    (Button::South, PadButton::South),
+    (Button::East, PadButton::East),
+    (Button::West, PadButton::West),
+    (Button::LeftTrigger2, PadButton::LeftTrigger),
+    (Button::RightTrigger2, PadButton::RightTrigger),
+    (Button::Start, PadButton::Start),
+];
+
+// every connected gamepad at once, sampled once per rendered frame; macroquad 0.4 does not read gamepads, so they
+// come through gilrs with the `gamepad` feature, without it nothing is ever down
+#[derive(Default)]
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<Gilrs>,
+    down: Vec<PadButton>,
+    pressed: Vec<PadButton>,    // went down this frame
+}
+
+impl Gamepads {
+    // reads nothing, for simulations driven from code
+    pub fn none() -> Gamepads {
+        Gamepads::default()
+    }
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Gamepads {
+        let gilrs = Gilrs::new().map_err(|err| eprintln!("could not open gamepads: {}", err)).ok();
+        Gamepads { gilrs, ..Gamepads::default() }
+    }
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Gamepads {
+        Gamepads::none()
+    }
+    pub fn is_down(&self, button: PadButton) -> bool { self.down.contains(&button) }
+    pub fn is_pressed(&self, button: PadButton) -> bool { self.pressed.contains(&button) }
+
+    pub fn poll(&mut self) {
+        let down = self.read();
+        self.pressed = down.iter().filter(|button| !self.down.contains(button)).copied().collect();
+        self.down = down;
+    }
+    #[cfg(feature = "gamepad")]
+    fn read(&mut self) -> Vec<PadButton> {
+        let Some(gilrs) = self.gilrs.as_mut() else { return Vec::new() };
+        // the state of a gamepad only moves on as its events are taken
+        while gilrs.next_event().is_some() {}
+        let mut down = Vec::new();
+        for (_, pad) in gilrs.gamepads() {
+            down.extend(BUTTONS.iter().filter(|(button, _)| pad.is_pressed(*button)).map(|(_, pad_button)| *pad_button));
+            let (x, y) = (pad.value(Axis::LeftStickX), pad.value(Axis::LeftStickY));
+            if x < -STICK_DEADZONE { down.push(PadButton::Left) }
+            if x > STICK_DEADZONE { down.push(PadButton::Right) }
+            if y > STICK_DEADZONE { down.push(PadButton::Up) }
+            if y < -STICK_DEADZONE { down.push(PadButton::Down) }
+        }
+        down
+    }
+    #[cfg(not(feature = "gamepad"))]
+    fn read(&mut self) -> Vec<PadButton> {
+        Vec::new()
+    }
+}
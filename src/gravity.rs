@@ -0,0 +1,57 @@
+use macroquad::color::Color;
+use macroquad::math::Vec2;
+use macroquad::prelude::{draw_circle, draw_circle_lines};
+use rand::Rng;
+use crate::ecs::toroidal_offset;
+
+const HORIZON_COLOR: Color = Color::new(0.7f32, 0.4f32, 1f32, 1f32);
+const FIELD_COLOR: Color = Color::new(0.7f32, 0.4f32, 1f32, 0.15f32);
+const MIN_SPAWN_DISTANCE: f32 = 250f32;     // random wells keep this far from the player spawn
+
+// a point mass pulling with strength / distance², anything closer than horizon is swallowed
+#[derive(Debug, Clone, PartialEq)]
+pub struct GravityWell {
+    pub pos: Vec2,
+    pub strength: f32,  // acceleration in pixels per second² at a distance of one pixel
+    pub horizon: f32,
+}
+
+impl GravityWell {
+    pub fn new(pos: Vec2, strength: f32, horizon: f32) -> GravityWell {
+        GravityWell { pos, strength, horizon }
+    }
+    pub fn random(arena: Vec2, keep_clear: Vec2, rng: &mut impl Rng) -> GravityWell {
+        let mut pos = Vec2::ZERO;
+        for _attempt in 0..32 {
+            pos = Vec2::new(rng.gen_range(0f32..arena.x), rng.gen_range(0f32..arena.y));
+            if toroidal_offset(pos - keep_clear, arena).length() > MIN_SPAWN_DISTANCE { break }
+        }
+        GravityWell::new(pos, rng.gen_range(2_000_000f32..6_000_000f32), rng.gen_range(14f32..24f32))
+    }
+
+    // pull on something at pos, the short way around the arena; capped at the horizon so it never blows up
+    pub fn acceleration(&self, pos: Vec2, arena: Vec2) -> Vec2 {
+        let offset = toroidal_offset(self.pos - pos, arena);
+        let distance = offset.length().max(self.horizon);
+        offset.normalize_or_zero() * self.strength / (distance * distance)
+    }
+    pub fn swallows(&self, pos: Vec2, arena: Vec2) -> bool {
+        toroidal_offset(self.pos - pos, arena).length() < self.horizon
+    }
+
+    pub fn draw(&self) {
+        // rings where the pull is 1000, 300 and 100 pixels per second²
+        for pull in [1000f32, 300f32, 100f32] {
+            let radius = (self.strength / pull).sqrt();
+            if radius > self.horizon {
+                draw_circle_lines(self.pos.x, self.pos.y, radius, 1f32, FIELD_COLOR);
+            }
+        }
+        draw_circle(self.pos.x, self.pos.y, self.horizon, Color::new(0f32, 0f32, 0f32, 1f32));
+        draw_circle_lines(self.pos.x, self.pos.y, self.horizon, 2f32, HORIZON_COLOR);
+    }
+}
+
+pub fn total_acceleration(wells: &[GravityWell], pos: Vec2, arena: Vec2) -> Vec2 {
+    wells.iter().fold(Vec2::ZERO, |sum, well| sum + well.acceleration(pos, arena))
+}
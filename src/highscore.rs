@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::Path;
+
+pub const HIGH_SCORES_PATH: &str = "res/highscores.txt";
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_NAME_LEN: usize = 12;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub score: i32,
+    pub ship: String,
+    pub time: f32,      // seconds survived
+    pub name: String,   // typed in on the game over screen, may be empty
+}
+
+// the best runs, highest score first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn get_entries(&self) -> &[HighScore] { &self.entries }
+
+    // where a score would end up in the table, None if it does not make it
+    pub fn rank_of(&self, score: i32) -> Option<usize> {
+        if score <= 0 { return None }
+        let rank = self.entries.iter().position(|entry| score > entry.score).unwrap_or(self.entries.len());
+        match rank {
+            _ if rank < MAX_HIGH_SCORES => Some(rank),
+            _ => None,
+        }
+    }
+
+    pub fn with_run(&self, run: HighScore) -> HighScores {
+        let mut scores = self.clone();
+        if let Some(rank) = scores.rank_of(run.score) {
+            scores.entries.insert(rank, run);
+            scores.entries.truncate(MAX_HIGH_SCORES);
+        }
+        scores
+    }
+
+    // a missing file is an empty table
+    pub fn load(path: impl AsRef<Path>) -> Result<HighScores, String> {
+        let path = path.as_ref();
+        if !path.exists() { return Ok(HighScores::default()) }
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        HighScores::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        }
+        fs::write(path, self.to_text()).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# asteroids high scores, score ship seconds name\n");
+        for entry in self.entries.iter() {
+            text += format!("score {} {} {} {}", entry.score, entry.ship, entry.time, entry.name).trim_end();
+            text += "\n";
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<HighScores, String> {
+        let mut scores = HighScores::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let entry = match words.as_slice() {
+                ["score", score, ship, time, name @ ..] => match (score.parse(), time.parse()) {
+                    (Ok(score), Ok(time)) => Ok(HighScore { score, ship: ship.to_string(), time, name: name.join(" ") }),
+                    _ => Err("score and time have to be numbers".to_string()),
+                },
+                ["score", ..] => Err("expected `score <score> <ship> <seconds> [name]`".to_string()),
+                [word, ..] => Err(format!("unknown entry '{}'", word)),
+                [] => continue,
+            };
+            scores = scores.with_run(entry.map_err(|err| format!("line {}: {}", idx + 1, err))?);
+        }
+        Ok(scores)
+    }
+}
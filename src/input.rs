@@ -0,0 +1,113 @@
+use macroquad::input::{is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, KeyCode, MouseButton};
+use macroquad::input::KeyCode::{A, D, Down, Left, Right, S, Space, Up, W};
+use crate::gamepad::{Gamepads, PadButton};
+
+pub const FIRE_BUFFER: f32 = 0.15f32;  // seconds a fire press waits for the weapon to cool down
+
+// what the keys do in a run, whatever the flight model makes of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Left,
+    Right,
+    Thrust,
+    Brake,
+    Fire,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [Action::Left, Action::Right, Action::Thrust, Action::Brake, Action::Fire];
+
+    pub fn get_keys(&self) -> &'static [KeyCode] {
+        match self {
+            Action::Left => &[Left, A],
+            Action::Right => &[Right, D],
+            Action::Thrust => &[Up, W],
+            Action::Brake => &[Down, S],
+            Action::Fire => &[Space],
+        }
+    }
+    pub fn get_buttons(&self) -> &'static [PadButton] {
+        match self {
+            Action::Left => &[PadButton::Left],
+            Action::Right => &[PadButton::Right],
+            Action::Thrust => &[PadButton::Up, PadButton::RightTrigger],
+            Action::Brake => &[PadButton::Down, PadButton::LeftTrigger],
+            Action::Fire => &[PadButton::South],
+        }
+    }
+    // a press and release within one frame still counts as down for that frame
+    pub fn is_down(&self, mouse_fires: bool, gamepads: &Gamepads) -> bool {
+        let keys = self.get_keys().iter().any(|key| is_key_down(*key) || is_key_pressed(*key));
+        let mouse = mouse_fires && *self == Action::Fire
+            && (is_mouse_button_down(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Left));
+        let buttons = self.get_buttons().iter().any(|button| gamepads.is_down(*button));
+        keys || mouse || buttons
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    Up,
+    Pressed,    // went down this frame
+    Held,
+    Released,   // went up this frame
+    Blocked,    // was down when the phase changed, ignored until it is let go
+}
+
+impl ButtonState {
+    fn next(&self, down: bool) -> ButtonState {
+        match self {
+            ButtonState::Blocked if down => ButtonState::Blocked,
+            ButtonState::Pressed | ButtonState::Held if down => ButtonState::Held,
+            ButtonState::Pressed | ButtonState::Held => ButtonState::Released,
+            _ if down => ButtonState::Pressed,
+            _ => ButtonState::Up,
+        }
+    }
+}
+
+// the actions sampled once per rendered frame, so ticks read a consistent state and short taps are not lost between them
+#[derive(Debug, Clone)]
+pub struct InputState {
+    states: [ButtonState; Action::ALL.len()],
+    fire_buffer: f32,   // game seconds a fire press is still waiting
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState { states: [ButtonState::Up; Action::ALL.len()], fire_buffer: 0f32 }
+    }
+    pub fn get(&self, action: Action) -> ButtonState { self.states[action as usize] }
+    pub fn pressed(&self, action: Action) -> bool { self.get(action) == ButtonState::Pressed }
+    pub fn held(&self, action: Action) -> bool { matches!(self.get(action), ButtonState::Pressed | ButtonState::Held) }
+    pub fn released(&self, action: Action) -> bool { self.get(action) == ButtonState::Released }
+
+    pub fn sample(&mut self, is_down: impl Fn(Action) -> bool) {
+        for action in Action::ALL {
+            self.states[action as usize] = self.get(action).next(is_down(action));
+        }
+        if self.pressed(Action::Fire) {
+            self.fire_buffer = FIRE_BUFFER;
+        }
+    }
+    // after a phase change keys that are still down from before do nothing until they are let go
+    pub fn debounce(&mut self) {
+        self.states = [ButtonState::Blocked; Action::ALL.len()];
+        self.fire_buffer = 0f32;
+    }
+    // held, or tapped a moment ago while the weapon was still cooling down
+    pub fn wants_fire(&self) -> bool {
+        self.held(Action::Fire) || self.fire_buffer > 0f32
+    }
+    pub fn take_fire(&mut self) {
+        self.fire_buffer = 0f32;
+    }
+    // once per tick
+    pub fn age(&mut self, delta_time: f32) {
+        self.fire_buffer = (self.fire_buffer - delta_time).max(0f32);
+    }
+}
+
+impl Default for InputState {
+    fn default() -> Self { InputState::new() }
+}
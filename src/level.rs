@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::Path;
+use macroquad::math::Vec2;
+use crate::comet::{Comet, Size, ROUGHNESS};
+use crate::gravity::GravityWell;
+use crate::shape::Shape;
+
+// one comet of a hand made starting layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct CometSpec {
+    pub size: Size,
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub rot_speed: f32,
+    pub sides: u32,
+    pub radius: f32,
+    pub rotation: f32,
+    pub roughness: f32,     // 0 is a regular polygon
+    pub seed: u64,          // picks the jagged outline
+}
+
+impl CometSpec {
+    // spec with the kind of outline a spawned comet of this size would have, standing still
+    pub fn new(size: Size, pos: Vec2, seed: u64) -> CometSpec {
+        let (sides, radius) = size.get_sides_and_radius();
+        CometSpec {
+            size,
+            pos,
+            vel: Vec2::ZERO,
+            rot_speed: 0f32,
+            sides,
+            radius,
+            rotation: 0f32,
+            roughness: ROUGHNESS,
+            seed,
+        }
+    }
+    pub fn get_shape(&self) -> Shape {
+        let mut shape = Shape::asteroid(self.pos, self.sides, self.radius, self.roughness, self.seed, 2f32);
+        shape.set_rot(self.rotation);
+        shape
+    }
+    pub fn to_comet(&self) -> Comet {
+        Comet::from_parts(self.size.clone(), self.vel, self.rot_speed, self.get_shape())
+    }
+}
+
+// starting layout for the custom level mode, stored as plain text:
+//
+//   player 640 360
+//   comet size 3 pos 100 120 vel 60 0 rot_speed 0.9 sides 13 radius 50 rotation 0 roughness 0.35 seed 7
+//   well pos 640 360 strength 4000000 horizon 20
+//
+// velocities and rot_speed are per second, lines starting with # are comments, keys of a
+// comet line may come in any order and missing ones fall back to the defaults of the comet's size,
+// except roughness which is 0 (a regular polygon) when left out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub player_spawn: Vec2,
+    pub comets: Vec<CometSpec>,
+    pub wells: Vec<GravityWell>,
+}
+
+impl Layout {
+    pub fn new(player_spawn: Vec2) -> Layout {
+        Layout {
+            player_spawn,
+            comets: Vec::new(),
+            wells: Vec::new(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Layout, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Layout::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        }
+        fs::write(path, self.to_text()).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# asteroids custom level\n");
+        text += &format!("player {} {}\n", self.player_spawn.x, self.player_spawn.y);
+        for comet in self.comets.iter() {
+            text += &format!("comet size {} pos {} {} vel {} {} rot_speed {} sides {} radius {} rotation {} roughness {} seed {}\n",
+                             comet.size.get_level(), comet.pos.x, comet.pos.y, comet.vel.x, comet.vel.y,
+                             comet.rot_speed, comet.sides, comet.radius, comet.rotation, comet.roughness, comet.seed);
+        }
+        for well in self.wells.iter() {
+            text += &format!("well pos {} {} strength {} horizon {}\n", well.pos.x, well.pos.y, well.strength, well.horizon);
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Layout, String> {
+        let mut layout = Layout::new(Vec2::ZERO);
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+            let mut words = line.split_whitespace();
+            let result = match words.next() {
+                Some("player") => parse_vec(&mut words).map(|pos| layout.player_spawn = pos),
+                Some("comet") => parse_comet(&mut words).map(|comet| layout.comets.push(comet)),
+                Some("well") => parse_well(&mut words).map(|well| layout.wells.push(well)),
+                Some(word) => Err(format!("unknown entry '{}'", word)),
+                None => Ok(()),
+            };
+            result.map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        Ok(layout)
+    }
+}
+
+fn parse_f32<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<f32, String> {
+    let word = words.next().ok_or("missing number")?;
+    word.parse().map_err(|_| format!("'{}' is not a number", word))
+}
+
+fn parse_vec<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<Vec2, String> {
+    Ok(Vec2::new(parse_f32(words)?, parse_f32(words)?))
+}
+
+fn parse_comet<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<CometSpec, String> {
+    let (mut size, mut pos, mut vel, mut rot_speed) = (Size::Three, Vec2::ZERO, Vec2::ZERO, 0f32);
+    let (mut sides, mut radius, mut rotation) = (None, None, 0f32);
+    let (mut roughness, mut seed) = (0f32, 0u64);
+    while let Some(key) = words.next() {
+        match key {
+            "size" => {
+                let level = parse_f32(words)? as i64;
+                size = Size::from_level(level).ok_or(format!("size {} does not exist", level))?;
+            }
+            "pos" => pos = parse_vec(words)?,
+            "vel" => vel = parse_vec(words)?,
+            "rot_speed" => rot_speed = parse_f32(words)?,
+            "sides" => sides = Some((parse_f32(words)? as u32).max(3)),
+            "radius" => radius = Some(parse_f32(words)?),
+            "rotation" => rotation = parse_f32(words)?,
+            "roughness" => roughness = parse_f32(words)?.clamp(0f32, 1f32),
+            "seed" => {
+                let word = words.next().ok_or("missing number")?;
+                seed = word.parse().map_err(|_| format!("'{}' is not a seed", word))?;
+            }
+            _ => return Err(format!("unknown comet key '{}'", key)),
+        }
+    }
+    let mut comet = CometSpec::new(size, pos, seed);
+    comet.roughness = roughness;
+    comet.vel = vel;
+    comet.rot_speed = rot_speed;
+    comet.rotation = rotation;
+    if let Some(sides) = sides { comet.sides = sides }
+    if let Some(radius) = radius { comet.radius = radius }
+    Ok(comet)
+}
+
+fn parse_well<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<GravityWell, String> {
+    let mut well = GravityWell::new(Vec2::ZERO, 4_000_000f32, 20f32);
+    while let Some(key) = words.next() {
+        match key {
+            "pos" => well.pos = parse_vec(words)?,
+            "strength" => well.strength = parse_f32(words)?,
+            "horizon" => well.horizon = parse_f32(words)?.max(1f32),
+            _ => return Err(format!("unknown well key '{}'", key)),
+        }
+    }
+    Ok(well)
+}
@@ -0,0 +1,27 @@
+pub mod player;
+pub mod comet;
+pub mod shape;
+pub mod projectile;
+pub mod game_state;
+pub mod env;
+pub mod script;
+pub mod level;
+pub mod editor;
+pub mod ecs;
+pub mod viewport;
+pub mod camera;
+pub mod debug;
+pub mod console;
+pub mod rewind;
+pub mod ship;
+pub mod gravity;
+pub mod scoring;
+pub mod stats;
+pub mod achievement;
+pub mod event;
+pub mod highscore;
+pub mod input;
+pub mod gamepad;
+pub mod scene;
+pub mod screens;
+pub mod ui;
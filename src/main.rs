@@ -1,32 +1,80 @@
-mod player;
-mod comet;
-mod shape;
-mod projectile;
-mod game_state;
-
-use macroquad::input::KeyCode::*;
 use macroquad::prelude::*;
-use crate::game_state::GameState;
+use asteroids::game_state::GameState;
+use asteroids::player::FlightModel;
+use asteroids::scene::SceneStack;
+use asteroids::screens::TitleScene;
+use asteroids::viewport::{ScaleMode, PLAYFIELD};
 
 
-const FPS: i32 = 30;    //fps for physics
-const TIME_PER_FRAME: f32 = 1f32 / FPS as f32;
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Asteroids".to_string(),
+        window_width: PLAYFIELD.x as i32,
+        window_height: PLAYFIELD.y as i32,
+        window_resizable: true,
+        ..Default::default()
+    }
+}
 
-#[macroquad::main("Asteroids")]
+#[macroquad::main(window_conf)]
 async fn main() {
     let font = load_ttf_font("res/RubikDoodleShadow-Regular.ttf").await.unwrap();
 
     let mut game_state = GameState::new(font);
+    // optional physics rate, ship, flight model, gravity wells, scaling, rewind seconds and level script,
+    // e.g. `cargo run -- --tick-rate=120 --ship=tank --flight=newtonian --wells=2 --rewind=20 --scale=integer --world=3840x2160 res/levels/waves.rhai`
+    for arg in std::env::args().skip(1) {
+        if let Some(rate) = arg.strip_prefix("--tick-rate=") {
+            match rate.parse() {
+                Ok(rate) => game_state.set_tick_rate(rate),
+                Err(_) => eprintln!("invalid tick rate: {}", rate),
+            }
+        } else if let Some(size) = arg.strip_prefix("--world=") {
+            match size.split_once('x').map(|(w, h)| (w.parse(), h.parse())) {
+                Some((Ok(w), Ok(h))) => game_state.set_world_size(Vec2::new(w, h)),
+                _ => eprintln!("invalid world size: {}, use e.g. 3840x2160", size),
+            }
+        } else if let Some(seconds) = arg.strip_prefix("--rewind=") {
+            match seconds.parse() {
+                Ok(seconds) => game_state.set_rewind_seconds(seconds),
+                Err(_) => eprintln!("invalid rewind duration: {}", seconds),
+            }
+        } else if let Some(count) = arg.strip_prefix("--wells=") {
+            match count.parse() {
+                Ok(count) => game_state.set_random_wells(count),
+                Err(_) => eprintln!("invalid number of gravity wells: {}", count),
+            }
+        } else if let Some(name) = arg.strip_prefix("--ship=") {
+            if let Err(err) = game_state.select_ship(name) {
+                eprintln!("{}", err);
+            }
+        } else if let Some(name) = arg.strip_prefix("--flight=") {
+            match FlightModel::from_name(name) {
+                Some(model) => game_state.set_flight_model(model),
+                None => eprintln!("invalid flight model: {}, use classic, newtonian or twinstick", name),
+            }
+        } else if let Some(mode) = arg.strip_prefix("--scale=") {
+            match ScaleMode::from_name(mode) {
+                Some(mode) => game_state.set_scale_mode(mode),
+                None => eprintln!("invalid scale mode: {}, use letterbox, stretch or integer", mode),
+            }
+        } else if let Err(err) = game_state.load_script(&arg) {
+            eprintln!("could not load level script: {}", err);
+        }
+    }
 
+    let mut scenes = SceneStack::new(Box::new(TitleScene::new()), &mut game_state);
     let mut lag = 0f32;
     loop {
-        game_state.refresh_all_cool_downs(get_frame_time());
-        lag += get_frame_time();
-        while lag >= TIME_PER_FRAME {
-            game_state.update();
-            lag -= TIME_PER_FRAME;
+        scenes.frame_inputs(&mut game_state, get_frame_time());
+        // time controls only stretch game time, the console, menus and editor keep running in real time
+        lag += game_state.scale_frame_time(get_frame_time());
+        let time_per_tick = game_state.get_time_per_tick();
+        while lag >= time_per_tick {
+            scenes.update(&mut game_state);
+            lag -= time_per_tick;
         }
-        game_state.draw();
+        scenes.draw(&game_state, lag / time_per_tick);
 
         next_frame().await;
     }
@@ -1,23 +1,109 @@
-use macroquad::color::{Color, WHITE};
+use macroquad::color::Color;
 use macroquad::math::{Vec2};
-use macroquad::shapes::{draw_triangle_lines};
-use macroquad::window::{screen_height, screen_width};
-use crate::shape::Shape;
+use macroquad::shapes::draw_line;
+use crate::ecs::wrap_position;
+use crate::ecs::Hitbox;
+use crate::shape::{circle_overlaps_polygon, polygon_contains, polygons_overlap, Shape};
+use crate::ship::{HitShape, ShipClass};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlightModel {
+    Classic,    // drag and a speed cap, the original feel
+    Newtonian,  // no drag and no speed cap, only thrust changes the velocity
+    TwinStick,  // move in any direction and aim separately, turning is instant
+}
+
+// all in pixels, radians and seconds
+struct FlightParams {
+    thrust: f32,
+    drag: f32,
+    max_speed: f32,
+    angular_thrust: f32,
+    angular_drag: f32,
+    max_angular_speed: f32,
+}
+
+impl FlightModel {
+    pub const ALL: [FlightModel; 3] = [FlightModel::Classic, FlightModel::Newtonian, FlightModel::TwinStick];
+
+    pub fn from_name(name: &str) -> Option<FlightModel> {
+        FlightModel::ALL.into_iter().find(|model| model.get_name() == name)
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            FlightModel::Classic => "classic",
+            FlightModel::Newtonian => "newtonian",
+            FlightModel::TwinStick => "twinstick",
+        }
+    }
+    pub fn next(&self) -> FlightModel {
+        match self {
+            FlightModel::Classic => FlightModel::Newtonian,
+            FlightModel::Newtonian => FlightModel::TwinStick,
+            FlightModel::TwinStick => FlightModel::Classic,
+        }
+    }
+    // the ship's stats describe it under the classic model, the others derive theirs from them
+    fn params(&self, ship: &ShipClass) -> FlightParams {
+        match self {
+            FlightModel::Classic => FlightParams {
+                thrust: ship.thrust,
+                drag: 180f32,
+                max_speed: ship.max_speed,
+                angular_thrust: ship.rotation_rate * 9f32,
+                angular_drag: ship.rotation_rate * 9f32,
+                max_angular_speed: ship.rotation_rate,
+            },
+            // the small angular drag stands in for reaction wheels, without it the ship would be unflyable
+            FlightModel::Newtonian => FlightParams {
+                thrust: ship.thrust * 0.5f32,
+                drag: 0f32,
+                max_speed: f32::INFINITY,
+                angular_thrust: ship.rotation_rate * 4.5f32,
+                angular_drag: ship.rotation_rate * 0.45f32,
+                max_angular_speed: ship.rotation_rate * 1.33f32,
+            },
+            // turning ignores the angular values and happens at max_angular_speed right away
+            FlightModel::TwinStick => FlightParams {
+                thrust: ship.thrust * 2.67f32,
+                drag: ship.thrust * 1.33f32,
+                max_speed: ship.max_speed * 1.07f32,
+                angular_thrust: 0f32,
+                angular_drag: 0f32,
+                max_angular_speed: ship.rotation_rate * 1.33f32,
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Player {
     pos: Vec2,
     vel: Vec2,
     dir: Vec2,
-    hit_box_radius: f32,
+    ang_vel: f32,       // radians per second
+    turning: bool,      // angular drag only slows the ship down on ticks without turn input
+    model: FlightModel,
+    prev_pos: Vec2,
+    prev_dir: Vec2,
+    ship: ShipClass,
 }
 
 impl Player {
-    pub fn new() -> Player {
+    pub fn new(arena: Vec2) -> Player {
+        Player::at(arena / 2f32)
+    }
+    pub fn at(pos: Vec2) -> Player {
         Player {
-            pos: Vec2::new(screen_width() / 2f32, screen_height() / 2f32),
+            pos,
             vel: Vec2::splat(0f32),
             dir: Vec2::new(0f32, -1f32),
-            hit_box_radius: 10f32,
+            ang_vel: 0f32,
+            turning: false,
+            model: FlightModel::Classic,
+            prev_pos: pos,
+            prev_dir: Vec2::new(0f32, -1f32),
+            ship: ShipClass::builtin()[0].clone(),
         }
     }
     pub fn get_dir(&self) -> Vec2 {
@@ -26,52 +112,141 @@ impl Player {
     pub fn get_pos(&self) -> Vec2 {
         self.pos
     }
-    pub fn update(&mut self) {
-        self.pos += self.vel;
-        if self.vel.length() > 0.2f32 {
-            self.vel -= 0.2f32*self.vel.normalize();
+    pub fn get_vel(&self) -> Vec2 {
+        self.vel
+    }
+    // bounding radius of the hit shape
+    pub fn get_hit_box_radius(&self) -> f32 {
+        self.ship.get_hit_radius()
+    }
+    pub fn get_ship(&self) -> &ShipClass {
+        &self.ship
+    }
+    pub fn set_ship(&mut self, ship: ShipClass) {
+        self.ship = ship;
+    }
+    pub fn get_ang_vel(&self) -> f32 {
+        self.ang_vel
+    }
+    pub fn get_flight_model(&self) -> FlightModel {
+        self.model
+    }
+    pub fn set_flight_model(&mut self, model: FlightModel) {
+        self.model = model;
+        self.ang_vel = 0f32;
+    }
+    pub fn update(&mut self, arena: Vec2, delta_time: f32) {
+        let params = self.model.params(&self.ship);
+        self.pos += self.vel * delta_time;
+        if self.vel.length() > params.drag * delta_time {
+            self.vel -= params.drag * delta_time * self.vel.normalize();
         } else {
             self.vel = Vec2::ZERO;
         }
-        match (self.pos.x, self.pos.y) {
-            (w, _) if w <= 0f32 => self.pos.x = screen_width(),
-            (w, _) if w >= screen_width() => self.pos.x = 0f32,
-            (_, h) if h <= 0f32 => self.pos.y = screen_height(),
-            (_, h) if h >= screen_height() => self.pos.y = 0f32,
-            _ => ()
+        self.rotate(self.ang_vel * delta_time);
+        match self.ang_vel.abs() {
+            _ if self.turning => (),
+            speed if speed > params.angular_drag * delta_time => self.ang_vel -= params.angular_drag * delta_time * self.ang_vel.signum(),
+            _ => self.ang_vel = 0f32,
+        }
+        self.turning = false;
+        let wrapped = wrap_position(self.pos, arena, 0f32);
+        self.prev_pos += wrapped - self.pos;
+        self.pos = wrapped;
+    }
+    // has to run before the inputs of a tick, they already turn the ship
+    pub fn store_previous(&mut self) {
+        self.prev_pos = self.pos;
+        self.prev_dir = self.dir;
+    }
+    pub fn draw(&self, color: Color, alpha: f32) {
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+        let dir = self.prev_dir.lerp(self.dir, alpha).try_normalize().unwrap_or(self.dir);
+        draw_hull(&self.ship.hull, pos, dir, color);
+    }
+    // ship space to world space at the current tick
+    pub fn to_world(&self, local: Vec2) -> Vec2 {
+        self.pos + self.dir * local.x + self.dir.perp() * local.y
+    }
+    // the hit shape in ship space, placed at pos and turned towards dir
+    pub fn get_hitbox(&self) -> Hitbox {
+        match self.ship.hit {
+            HitShape::Circle(_) => Hitbox::Circle { radius: self.get_hit_box_radius() },
+            HitShape::Hull => Hitbox::Polygon { vertices: self.ship.hull.clone() },
         }
     }
-    pub fn draw(&self, color: Color) {
-        // hardcoded with collision !!!!!
-        let perp = self.dir.perp().normalize();
-        let (a, b, c) = (self.pos-self.dir*10f32-perp*10f32,
-                         self.pos-self.dir*10f32+perp*10f32,
-                         self.pos+self.dir*20f32);
-        draw_triangle_lines(a, b, c, 2f32, color);
+    pub fn get_hit_outline(&self) -> Option<Vec<Vec2>> {
+        match self.ship.hit {
+            HitShape::Circle(_) => None,
+            HitShape::Hull => Some(self.ship.hull.iter().map(|point| self.to_world(*point)).collect()),
+        }
     }
     pub fn rotate(&mut self, angle: f32) {
         self.dir = Vec2::new(angle.cos()*self.dir.x - angle.sin()*self.dir.y,
         angle.sin()*self.dir.x + angle.cos()*self.dir.y).normalize_or_zero()
     }
-    pub fn accelerate(&mut self, factor: f32) {
-        self.vel += self.dir * factor;
-        if self.vel.length() > 10f32 {
-            self.vel = self.vel.normalize()*10f32;
+    // input from -1 (left) to 1 (right), spins the ship up instead of turning it, except for twin-stick
+    pub fn turn(&mut self, input: f32, delta_time: f32) {
+        let params = self.model.params(&self.ship);
+        match self.model {
+            FlightModel::TwinStick => self.rotate(input * params.max_angular_speed * delta_time),
+            _ => {
+                self.turning = true;
+                self.ang_vel += input * params.angular_thrust * delta_time;
+                self.ang_vel = self.ang_vel.clamp(-params.max_angular_speed, params.max_angular_speed);
+            }
+        }
+    }
+    // factor scales the thrust of the flight model, negative values brake
+    pub fn accelerate(&mut self, factor: f32, delta_time: f32) {
+        let params = self.model.params(&self.ship);
+        // thrusting never slows down a ship that something else flung past the cap
+        let cap = params.max_speed.max(self.vel.length());
+        self.vel += self.dir * factor * params.thrust * delta_time;
+        self.vel = self.vel.clamp_length_max(cap);
+    }
+    // twin-stick movement: the velocity points where the stick points right away, only the speed builds up
+    pub fn steer(&mut self, direction: Vec2, delta_time: f32) {
+        let Some(direction) = direction.try_normalize() else { return };
+        let params = self.model.params(&self.ship);
+        let speed = (self.vel.length() + params.thrust * delta_time).min(params.max_speed);
+        self.vel = direction * speed;
+    }
+    pub fn aim(&mut self, direction: Vec2) {
+        if let Some(direction) = direction.try_normalize() {
+            self.dir = direction;
         }
     }
     pub fn contains(&self, point: Vec2) -> bool {
-        let hit_box_radius = 10f32;
-        let abs_dist = (point - self.pos).length();
-        if abs_dist > hit_box_radius { return false }
-        return true
+        match self.get_hit_outline() {
+            Some(outline) => polygon_contains(&outline, point),
+            None => (point - self.pos).length() <= self.get_hit_box_radius(),
+        }
     }
     pub fn overlaps_shape(&self, shape: &Shape) -> bool {
-        let collision_distance = shape.get_rad() + self.hit_box_radius;
+        let collision_distance = shape.get_rad() + self.get_hit_box_radius();
         if (shape.get_pos() - self.pos).length() > collision_distance { return false }
-        true
+        let vertices = shape.get_world_vertices();
+        match self.get_hit_outline() {
+            Some(outline) => polygons_overlap(&outline, &vertices),
+            None => circle_overlaps_polygon(self.pos, self.get_hit_box_radius(), &vertices),
+        }
+    }
+    // outside forces like gravity, ignore the flight model's speed cap
+    pub fn apply_acceleration(&mut self, acceleration: Vec2, delta_time: f32) {
+        self.vel += acceleration * delta_time;
     }
     pub fn give_impulse(&mut self, direction: Vec2, power: f32) {
         let dir = direction.normalize();
         self.vel += dir * power;
     }
-}
\ No newline at end of file
+}
+
+// draws a ship outline at pos with its nose along dir, also used by the ship select screen
+pub fn draw_hull(hull: &[Vec2], pos: Vec2, dir: Vec2, color: Color) {
+    let to_world = |local: Vec2| pos + dir * local.x + dir.perp() * local.y;
+    for (idx, a) in hull.iter().enumerate() {
+        let (a, b) = (to_world(*a), to_world(hull[(idx + 1) % hull.len()]));
+        draw_line(a.x, a.y, b.x, b.y, 2f32, color);
+    }
+}
@@ -1,12 +1,14 @@
 use macroquad::color::WHITE;
 use macroquad::math::Vec2;
 use macroquad::shapes::draw_line;
-use macroquad::window::{screen_height, screen_width};
+use crate::ecs::is_outside;
 
+#[derive(Clone)]
 pub struct Projectile {
     pos: Vec2,
+    prev_pos: Vec2,
     dir: Vec2,
-    speed: f32,
+    speed: f32,     // pixels per second
     length: f32,
     alive: bool,
 }
@@ -16,28 +18,42 @@ impl Projectile {
             speed,
             dir: direction.normalize(),
             pos: position,
+            prev_pos: position,
             length: 10f32,
             alive: true,
         }
     }
-    pub fn update(&mut self) {
-        self.pos += self.dir * self.speed;
+    pub fn update(&mut self, delta_time: f32) {
+        self.pos += self.dir * self.speed * delta_time;
+    }
+    // bends the flight path, acceleration in pixels per second²
+    pub fn accelerate(&mut self, acceleration: Vec2, delta_time: f32) {
+        let vel = self.dir * self.speed + acceleration * delta_time;
+        self.speed = vel.length();
+        self.dir = vel.try_normalize().unwrap_or(self.dir);
     }
     pub fn destroy(&mut self) {self.alive = false}
 
     pub fn is_alive(&self) -> bool {self.alive}
-    pub fn draw(&self) {
-        let sec_point = self.pos + self.dir * self.length;
-        draw_line(self.pos.x, self.pos.y, sec_point.x, sec_point.y, 2f32, WHITE);
+    pub fn draw(&self, alpha: f32) {
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+        let sec_point = pos + self.dir * self.length;
+        draw_line(pos.x, pos.y, sec_point.x, sec_point.y, 2f32, WHITE);
+    }
+    pub fn store_previous(&mut self) {
+        self.prev_pos = self.pos;
+    }
+    pub fn get_pos(&self) -> Vec2 {
+        self.pos
+    }
+    pub fn get_dir(&self) -> Vec2 {
+        self.dir
     }
     pub fn get_tip_pos(&self) -> Vec2 {
         self.pos + self.dir * self.length
     }
 
-    pub fn is_off_screen(&self) -> bool{
-        let puffer = 100f32;
-        if self.pos.x < 0f32-puffer || self.pos.x > screen_width()+puffer {return true}
-        if self.pos.y < 0f32-puffer || self.pos.y > screen_height()+puffer {return true}
-        false
+    pub fn is_off_screen(&self, arena: Vec2) -> bool{
+        is_outside(self.pos, arena, 100f32)
     }
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+use rand::rngs::StdRng;
+use crate::achievement::RunProgress;
+use crate::camera::FollowCamera;
+use crate::comet::Comet;
+use crate::ecs::World;
+use crate::gravity::GravityWell;
+use crate::player::Player;
+use crate::projectile::Projectile;
+use crate::scoring::Scoring;
+use crate::stats::RunStats;
+
+pub const DEFAULT_REWIND_SECONDS: f32 = 10f32;
+
+// everything a tick changes, taken before the tick runs; level scripts keep their own state and are not rewound
+pub struct Snapshot {
+    pub player: Player,
+    pub comets: Vec<Comet>,
+    pub projectiles: Vec<Projectile>,
+    pub world: World,
+    pub wells: Vec<GravityWell>,
+    pub camera: FollowCamera,
+    pub rng: StdRng,
+    pub scoring: Scoring,
+    pub stats: RunStats,
+    pub achievements: RunProgress,
+    pub player_lives: u32,
+    pub weapon_cd: f32,
+    pub invincibility_timer: f32,
+    pub comet_spawn_timer: f32,
+    pub game_duration: f32,
+    pub messages: Vec<(String, f32)>,
+}
+
+// one snapshot per tick, the oldest are dropped once `seconds` worth are stored
+pub struct RewindBuffer {
+    snapshots: VecDeque<Snapshot>,
+    seconds: f32,
+    capacity: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(seconds: f32, time_per_tick: f32) -> RewindBuffer {
+        let mut buffer = RewindBuffer { snapshots: VecDeque::new(), seconds, capacity: 0 };
+        buffer.set_duration(seconds, time_per_tick);
+        buffer
+    }
+    pub fn get_seconds(&self) -> f32 { self.seconds }
+    pub fn len(&self) -> usize { self.snapshots.len() }
+    pub fn is_empty(&self) -> bool { self.snapshots.is_empty() }
+
+    // also has to be called when the tick rate changes, the buffer counts ticks
+    pub fn set_duration(&mut self, seconds: f32, time_per_tick: f32) {
+        self.seconds = seconds.max(0f32);
+        self.capacity = (self.seconds / time_per_tick).round() as usize;
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 { return }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+    // newest first, so popping repeatedly walks back in time
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
@@ -0,0 +1,140 @@
+use macroquad::color::Color;
+use macroquad::prelude::draw_rectangle;
+use macroquad::text::Font;
+use crate::game_state::GameState;
+
+const FADE_SECONDS: f32 = 0.2f32;  // each way, out of the old screen and into the new one
+
+// what the scene on top wants to happen to the stack
+pub enum Transition {
+    None,
+    Push(Box<dyn Scene>),
+    Pop,
+    Replace(Box<dyn Scene>),    // swaps the scene on top
+    Reset(Box<dyn Scene>),      // clears the whole stack, e.g. back to the title
+}
+
+// one screen, only the scene on top gets the inputs
+pub trait Scene {
+    fn enter(&mut self, _game: &mut GameState) {}
+    fn exit(&mut self, _game: &mut GameState) {}
+    // once per rendered frame, for keys that are pressed rather than held
+    fn frame_inputs(&mut self, _game: &mut GameState, _delta_time: f32) -> Transition { Transition::None }
+    // once per physics tick, before the tick
+    fn inputs(&mut self, _game: &mut GameState) {}
+    // once per physics tick, after the tick, to react to the simulation
+    fn update(&mut self, _game: &mut GameState) -> Transition { Transition::None }
+    // alpha as in GameState::draw_play
+    fn draw(&self, game: &GameState, font: &Font, alpha: f32);
+    // overlays are drawn on top of the scenes below instead of hiding them
+    fn is_overlay(&self) -> bool { false }
+}
+
+struct Fade {
+    time: f32,
+    pending: Option<Transition>,    // applied once the old screen has faded out
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    fade: Option<Fade>,
+}
+
+impl SceneStack {
+    pub fn new(mut first: Box<dyn Scene>, game: &mut GameState) -> SceneStack {
+        first.enter(game);
+        SceneStack { scenes: vec![first], fade: None }
+    }
+    pub fn len(&self) -> usize { self.scenes.len() }
+    pub fn is_empty(&self) -> bool { self.scenes.is_empty() }
+    pub fn is_fading(&self) -> bool { self.fade.is_some() }
+
+    // the console comes first, scenes do not get the keyboard while it is open or while fading
+    pub fn frame_inputs(&mut self, game: &mut GameState, delta_time: f32) {
+        let console = game.frame_inputs(delta_time);
+        if let Some(fade) = self.fade.as_mut() {
+            fade.time += delta_time;
+            if fade.time >= FADE_SECONDS {
+                if let Some(transition) = fade.pending.take() {
+                    self.apply(transition, game);
+                }
+            }
+            if self.fade.as_ref().is_some_and(|fade| fade.time >= 2f32 * FADE_SECONDS) {
+                self.fade = None;
+            }
+            return;
+        }
+        if console { return }
+        let Some(top) = self.scenes.last_mut() else { return };
+        let transition = top.frame_inputs(game, delta_time);
+        self.start(transition, game);
+    }
+
+    pub fn update(&mut self, game: &mut GameState) {
+        let Some(top) = self.scenes.last_mut() else { return };
+        game.update(|game| top.inputs(game));
+        let transition = top.update(game);
+        self.start(transition, game);
+    }
+
+    pub fn draw(&self, game: &GameState, alpha: f32) {
+        game.draw(|font| {
+            // everything from the topmost full screen scene upwards is visible
+            let first = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+            for scene in self.scenes[first..].iter() {
+                scene.draw(game, font, alpha);
+            }
+            if let Some(fade) = self.fade.as_ref() {
+                let view = game.get_view_size();
+                let opacity = 1f32 - (fade.time / FADE_SECONDS - 1f32).abs();
+                draw_rectangle(0f32, 0f32, view.x, view.y, Color::new(0f32, 0f32, 0f32, opacity.clamp(0f32, 1f32)));
+            }
+        });
+    }
+
+    // overlays come and go at once, anything that changes the whole screen fades through black
+    fn start(&mut self, transition: Transition, game: &mut GameState) {
+        let fades = match &transition {
+            Transition::None => return,
+            Transition::Push(scene) => !scene.is_overlay(),
+            Transition::Pop => self.scenes.last().is_some_and(|scene| !scene.is_overlay()),
+            Transition::Replace(_) | Transition::Reset(_) => true,
+        };
+        match fades {
+            true if self.fade.is_some() => (),  // already on its way somewhere else
+            true => self.fade = Some(Fade { time: 0f32, pending: Some(transition) }),
+            false => self.apply(transition, game),
+        }
+    }
+
+    fn apply(&mut self, transition: Transition, game: &mut GameState) {
+        match transition {
+            Transition::None => (),
+            Transition::Push(mut scene) => {
+                scene.enter(game);
+                self.scenes.push(scene);
+            }
+            // the last scene stays, there has to be something on screen
+            Transition::Pop if self.scenes.len() > 1 => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(game);
+                }
+            }
+            Transition::Pop => (),
+            Transition::Replace(mut scene) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.exit(game);
+                }
+                scene.enter(game);
+                self.scenes.push(scene);
+            }
+            Transition::Reset(mut scene) => {
+                while let Some(mut old) = self.scenes.pop() {
+                    old.exit(game);
+                }
+                scene.enter(game);
+                self.scenes.push(scene);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use macroquad::color::{Color, WHITE};
+use macroquad::math::Vec2;
+use crate::event::{Cause, GameEvent, Subscriber};
+use crate::ui::{Anchor, Ui};
+
+const MISS_PENALTY: i32 = 1;
+const COMBO_STEP: u32 = 5;          // hits in a row per step of the multiplier
+const MAX_MULTIPLIER: u32 = 4;
+const ACCURACY_BONUS: f32 = 1000f32;    // bonus for a wave without a single miss
+const EXTRA_LIFE_EVERY: i32 = 10000;
+const POPUP_SECONDS: f32 = 1f32;
+const POPUP_RISE: f32 = 40f32;      // pixels per second
+
+// text floating up from where points were scored, in world coordinates
+#[derive(Clone)]
+pub struct Popup {
+    text: String,
+    pos: Vec2,
+    age: f32,
+    color: Color,
+}
+
+#[derive(Clone)]
+pub struct Scoring {
+    score: i32,
+    combo: u32,     // hits in a row without a miss
+    shots: u32,     // since the last wave ended
+    hits: u32,
+    next_extra_life: i32,
+    extra_lives: u32,   // earned but not yet handed to the player
+    wave_bonus: Option<(u32, i32)>,     // accuracy in percent and bonus of the last wave, until taken
+    popups: Vec<Popup>,
+}
+
+impl Scoring {
+    pub fn new() -> Scoring {
+        Scoring {
+            score: 0,
+            combo: 0,
+            shots: 0,
+            hits: 0,
+            next_extra_life: EXTRA_LIFE_EVERY,
+            extra_lives: 0,
+            wave_bonus: None,
+            popups: Vec::new(),
+        }
+    }
+    pub fn get_score(&self) -> i32 { self.score }
+    // moves the extra life thresholds along, setting the score never hands out lives
+    pub fn set_score(&mut self, score: i32) {
+        self.score = score;
+        self.next_extra_life = (score.max(0) / EXTRA_LIFE_EVERY + 1) * EXTRA_LIFE_EVERY;
+    }
+    pub fn get_combo(&self) -> u32 { self.combo }
+    pub fn get_multiplier(&self) -> u32 {
+        (1 + self.combo / COMBO_STEP).min(MAX_MULTIPLIER)
+    }
+
+    pub fn shot(&mut self) {
+        self.shots += 1;
+    }
+    // points for a hit at pos, multiplied by the combo the hit was part of; hits worth nothing
+    // still keep the combo going
+    pub fn hit(&mut self, points: i32, pos: Vec2) {
+        let multiplier = self.get_multiplier();
+        self.combo += 1;
+        self.hits += 1;
+        if points == 0 { return }
+        let points = points * multiplier as i32;
+        let text = match multiplier {
+            1 => format!("+{}", points),
+            _ => format!("+{} x{}", points, multiplier),
+        };
+        self.popup(text, pos, WHITE);
+        self.add(points);
+    }
+    pub fn miss(&mut self) {
+        self.combo = 0;
+        self.score -= MISS_PENALTY;
+    }
+    // points that count towards extra lives but not the combo
+    pub fn add(&mut self, points: i32) {
+        self.score += points;
+        while self.score >= self.next_extra_life {
+            self.extra_lives += 1;
+            self.next_extra_life += EXTRA_LIFE_EVERY;
+        }
+    }
+    // bonus for the share of shots since the last wave that hit something, None if nothing was fired
+    pub fn end_wave(&mut self) -> Option<(u32, i32)> {
+        let (shots, hits) = (self.shots, self.hits.min(self.shots));
+        self.shots = 0;
+        self.hits = 0;
+        if shots == 0 { return None }
+        let accuracy = hits as f32 / shots as f32;
+        let bonus = (accuracy * ACCURACY_BONUS).round() as i32;
+        self.add(bonus);
+        Some(((accuracy * 100f32).round() as u32, bonus))
+    }
+    pub fn take_extra_lives(&mut self) -> u32 {
+        std::mem::take(&mut self.extra_lives)
+    }
+    pub fn take_wave_bonus(&mut self) -> Option<(u32, i32)> {
+        self.wave_bonus.take()
+    }
+
+    pub fn popup(&mut self, text: String, pos: Vec2, color: Color) {
+        self.popups.push(Popup { text, pos, age: 0f32, color });
+    }
+    pub fn update(&mut self, delta_time: f32) {
+        for popup in self.popups.iter_mut() {
+            popup.age += delta_time;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_SECONDS);
+    }
+    // centred above where the points were made, in world space
+    pub fn draw_popups(&self, ui: &mut Ui) {
+        for popup in self.popups.iter() {
+            let mut color = popup.color;
+            color.a = 1f32 - popup.age / POPUP_SECONDS;
+            ui.pinned(Anchor::Bottom, popup.pos - Vec2::new(0f32, popup.age * POPUP_RISE), 0f32, |ui| {
+                ui.set_style(20, color);
+                ui.label(&popup.text);
+            });
+        }
+    }
+    // combo counter under the score while a multiplier is running
+    pub fn combo_label(&self) -> Option<String> {
+        match self.get_multiplier() {
+            1 => None,
+            multiplier => Some(format!("Combo {}  x{}", self.combo, multiplier)),
+        }
+    }
+}
+
+impl Subscriber for Scoring {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::ProjectileFired { .. } => self.shot(),
+            GameEvent::ProjectileMissed { .. } => self.miss(),
+            GameEvent::CometDestroyed { size, pos, by: Cause::Projectile, .. } => self.hit(size.get_points(), *pos),
+            GameEvent::EntityDestroyed { pos, points } => self.hit(*points, *pos),
+            GameEvent::WaveCleared => self.wave_bonus = self.end_wave(),
+            _ => (),
+        }
+    }
+}
+
+impl Default for Scoring {
+    fn default() -> Scoring {
+        Scoring::new()
+    }
+}
@@ -0,0 +1,601 @@
+use macroquad::color::{Color, GRAY, WHITE, YELLOW};
+use macroquad::input::is_key_pressed;
+use macroquad::input::KeyCode::{Escape, N, P};
+use macroquad::math::Vec2;
+use macroquad::prelude::draw_rectangle;
+use macroquad::text::Font;
+use crate::comet::Size;
+use crate::editor::{Editor, EditorAction, CUSTOM_LEVEL_PATH};
+use crate::gamepad::PadButton;
+use crate::game_state::{GamePhase, GameState};
+use crate::highscore::MAX_NAME_LEN;
+use crate::level::Layout;
+use crate::player::draw_hull;
+use crate::scene::{Scene, Transition};
+use crate::stats::format_time;
+use crate::ui::{Anchor, NavInput, Ui, DEFAULT_SIZE};
+
+const MAX_WELLS: f32 = 8f32;
+const REWIND_STEP: f32 = 5f32;     // seconds
+const MAX_REWIND: f32 = 60f32;
+
+// runs a menu against this frame's inputs, the same function draws it later in the frame
+fn interact<T>(game: &GameState, focus: &mut usize, menu: impl FnOnce(&mut Ui) -> T) -> (T, NavInput) {
+    let nav = NavInput::from_keyboard(game.get_typed()).with_gamepads(game.get_gamepads());
+    let mut ui = Ui::interact(game.get_view_size(), *focus, nav.clone());
+    let result = menu(&mut ui);
+    *focus = ui.finish();
+    (result, nav)
+}
+
+// darkens whatever is below an overlay
+fn dim(view: Vec2, opacity: f32) {
+    draw_rectangle(0f32, 0f32, view.x, view.y, Color::new(0f32, 0f32, 0f32, opacity));
+}
+
+enum TitleChoice {
+    Start,
+    CustomLevel,
+    Editor,
+    Achievements,
+    HighScores,
+    Settings,
+    FlightModel,
+}
+
+#[derive(Default)]
+pub struct TitleScene {
+    focus: usize,
+}
+
+impl TitleScene {
+    pub fn new() -> TitleScene { TitleScene::default() }
+
+    fn menu(ui: &mut Ui, game: &GameState) -> Option<TitleChoice> {
+        let mut choice = None;
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 110f32), 16f32, |ui| {
+            ui.set_style(90, WHITE);
+            ui.label("ASTEROIDS");
+            ui.space(20f32);
+            ui.set_style(DEFAULT_SIZE, WHITE);
+            let buttons = [
+                ("START", TitleChoice::Start),
+                ("CUSTOM LEVEL", TitleChoice::CustomLevel),
+                ("LEVEL EDITOR", TitleChoice::Editor),
+                ("ACHIEVEMENTS", TitleChoice::Achievements),
+                ("HIGH SCORES", TitleChoice::HighScores),
+                ("SETTINGS", TitleChoice::Settings),
+            ];
+            for (text, pick) in buttons {
+                if ui.button(text) { choice = Some(pick) }
+            }
+            if ui.choice("FLIGHT MODEL", &game.get_flight_model().get_name().to_uppercase()) != 0 {
+                choice = Some(TitleChoice::FlightModel);
+            }
+        });
+        choice
+    }
+}
+
+impl Scene for TitleScene {
+    fn enter(&mut self, game: &mut GameState) {
+        game.set_phase(GamePhase::MENU);
+    }
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (choice, _) = interact(game, &mut self.focus, |ui| TitleScene::menu(ui, game));
+        match choice {
+            Some(TitleChoice::Start) => {
+                game.set_custom_level(None);
+                Transition::Push(Box::new(SelectScene))
+            }
+            Some(TitleChoice::CustomLevel) => match Layout::load(CUSTOM_LEVEL_PATH) {
+                Ok(layout) => {
+                    game.set_custom_level(Some(layout));
+                    Transition::Push(Box::new(SelectScene))
+                }
+                Err(err) => {
+                    eprintln!("could not load custom level: {}", err);
+                    Transition::None
+                }
+            },
+            Some(TitleChoice::Editor) => Transition::Push(Box::new(EditorScene::new(game))),
+            Some(TitleChoice::Achievements) => Transition::Push(Box::new(AchievementsScene::new())),
+            Some(TitleChoice::HighScores) => Transition::Push(Box::new(HighScoresScene::new())),
+            Some(TitleChoice::Settings) => Transition::Push(Box::new(SettingsScene::new())),
+            Some(TitleChoice::FlightModel) => {
+                game.set_flight_model(game.get_flight_model().next());
+                Transition::None
+            }
+            None => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        TitleScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus), game);
+    }
+}
+
+// between the title and a new run
+pub struct SelectScene;
+
+impl Scene for SelectScene {
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let nav = NavInput::from_keyboard(game.get_typed()).with_gamepads(game.get_gamepads());
+        let count = game.get_ships().len();
+        if nav.left {
+            game.set_ship_index(game.get_ship_index() + count - 1);
+        }
+        if nav.right {
+            game.set_ship_index(game.get_ship_index() + 1);
+        }
+        if nav.accept {
+            Transition::Replace(Box::new(PlayScene))
+        } else if nav.back {
+            Transition::Pop
+        } else {
+            Transition::None
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        let view = game.get_view_size();
+        let spacing = view.x / (game.get_ships().len() + 1) as f32;
+        let mut ui = Ui::new(font, view, 0);
+        for (idx, ship) in game.get_ships().iter().enumerate() {
+            let color = if idx == game.get_ship_index() { WHITE } else { GRAY };
+            let pos = Vec2::new(spacing * (idx + 1) as f32, 320f32);
+            let hull: Vec<Vec2> = ship.hull.iter().map(|point| *point * 2.5f32).collect();
+            draw_hull(&hull, pos, Vec2::new(0f32, -1f32), color);
+            // the name centred below its hull
+            ui.anchored(Anchor::Top, Vec2::new(pos.x - view.x / 2f32, 396f32), 0f32, |ui| {
+                ui.set_style(30, color);
+                ui.label(&ship.name.to_uppercase());
+            });
+        }
+        let ship = game.get_ship();
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 120f32), 0f32, |ui| {
+            ui.set_style(50, WHITE);
+            ui.label("CHOOSE YOUR SHIP");
+        });
+        ui.anchored(Anchor::Bottom, Vec2::new(0f32, -120f32), 70f32, |ui| {
+            ui.set_style(24, WHITE);
+            ui.label(&format!("THRUST {}   TOP SPEED {}   TURN {}   LIVES {}   GUNS {}   SHOTS {:.1}/s",
+                              ship.thrust, ship.max_speed, ship.rotation_rate, ship.lives, ship.guns.len(), 1f32 / ship.cooldown));
+            ui.set_style(24, GRAY);
+            ui.label("LEFT / RIGHT: CHOOSE    ENTER: START    ESC: BACK");
+        });
+    }
+}
+
+// a run from start to game over, the game over screen goes on top of it
+pub struct PlayScene;
+
+impl Scene for PlayScene {
+    fn enter(&mut self, game: &mut GameState) {
+        game.start_run();
+    }
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        game.time_inputs();
+        match is_key_pressed(P) || is_key_pressed(Escape) || game.get_gamepads().is_pressed(PadButton::Start) {
+            true => Transition::Push(Box::new(PauseScene::new())),
+            false => Transition::None,
+        }
+    }
+    fn inputs(&mut self, game: &mut GameState) {
+        game.play_inputs();
+    }
+    fn update(&mut self, game: &mut GameState) -> Transition {
+        match game.get_phase() {
+            GamePhase::END => Transition::Push(Box::new(GameOverScene::new())),
+            _ => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, alpha: f32) {
+        game.draw_play(font, alpha);
+    }
+}
+
+enum PauseChoice {
+    Resume,
+    Step,
+    Settings,
+    Quit,
+}
+
+#[derive(Default)]
+pub struct PauseScene {
+    focus: usize,
+}
+
+impl PauseScene {
+    pub fn new() -> PauseScene { PauseScene::default() }
+
+    fn menu(ui: &mut Ui) -> Option<PauseChoice> {
+        let mut choice = None;
+        ui.anchored(Anchor::Centre, Vec2::ZERO, 16f32, |ui| {
+            ui.set_style(60, WHITE);
+            ui.label("PAUSED");
+            ui.space(10f32);
+            ui.set_style(DEFAULT_SIZE, WHITE);
+            let buttons = [
+                ("RESUME", PauseChoice::Resume),
+                ("STEP  (N)", PauseChoice::Step),
+                ("SETTINGS", PauseChoice::Settings),
+                ("QUIT TO MENU", PauseChoice::Quit),
+            ];
+            for (text, pick) in buttons {
+                if ui.button(text) { choice = Some(pick) }
+            }
+        });
+        choice
+    }
+}
+
+impl Scene for PauseScene {
+    fn enter(&mut self, game: &mut GameState) {
+        game.set_paused(true);
+    }
+    fn exit(&mut self, game: &mut GameState) {
+        game.set_paused(false);
+    }
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (choice, nav) = interact(game, &mut self.focus, PauseScene::menu);
+        match choice {
+            _ if nav.back || is_key_pressed(P) || game.get_gamepads().is_pressed(PadButton::Start) => Transition::Pop,
+            Some(PauseChoice::Resume) => Transition::Pop,
+            Some(PauseChoice::Settings) => Transition::Push(Box::new(SettingsScene::new())),
+            Some(PauseChoice::Quit) => Transition::Reset(Box::new(TitleScene::new())),
+            Some(PauseChoice::Step) => {
+                game.step(1);
+                Transition::None
+            }
+            None if is_key_pressed(N) => {
+                game.step(1);
+                Transition::None
+            }
+            None => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        dim(game.get_view_size(), 0.5f32);
+        PauseScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus));
+    }
+    fn is_overlay(&self) -> bool { true }
+}
+
+enum GameOverChoice {
+    Name(String),
+    Restart,
+    Quit,
+}
+
+// everything that happened during the run and the lifetime stats below, rewinding takes it away again
+#[derive(Default)]
+pub struct GameOverScene {
+    focus: usize,
+}
+
+impl GameOverScene {
+    pub fn new() -> GameOverScene { GameOverScene::default() }
+
+    // a new high score gets a name, typed into the first widget
+    fn names_score(game: &GameState) -> bool {
+        game.keeps_records() && game.get_high_score_rank().is_some()
+    }
+
+    fn menu(ui: &mut Ui, game: &GameState) -> Option<GameOverChoice> {
+        let stats = game.get_stats();
+        let mut choice = None;
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 90f32), 12f32, |ui| {
+            ui.set_style(50, WHITE);
+            ui.label("GAME OVER!");
+            ui.label(&format!("YOUR SCORE WAS: {}", game.get_score()));
+            ui.set_style(26, YELLOW);
+            match game.get_high_score_rank() {
+                Some(rank) if GameOverScene::names_score(game) => {
+                    ui.row(26f32, |ui| {
+                        ui.label(&format!("NEW HIGH SCORE  #{}", rank + 1));
+                        if let Some(name) = ui.text_input("NAME", game.get_player_name(), MAX_NAME_LEN) {
+                            choice = Some(GameOverChoice::Name(name));
+                        }
+                    });
+                }
+                _ => ui.space(26f32),
+            }
+            ui.set_style(26, WHITE);
+            ui.list(&[
+                format!("TIME SURVIVED {}   DISTANCE FLOWN {:.0}", format_time(stats.time), stats.distance),
+                format!("SHOTS {}   HITS {}   ACCURACY {:.0}%", stats.shots, stats.hits, stats.accuracy() * 100f32),
+                format!("COMETS DESTROYED   LARGE {}   MEDIUM {}   SMALL {}",
+                        stats.get_destroyed(&Size::Three), stats.get_destroyed(&Size::Two), stats.get_destroyed(&Size::One)),
+                format!("LIVES LOST {}   LONGEST STREAK {}", stats.lives_lost, stats.longest_streak),
+            ], None, WHITE);
+            if game.keeps_records() {
+                let lifetime = game.get_lifetime_stats();
+                let total = &lifetime.total;
+                ui.set_style(22, GRAY);
+                ui.list(&[
+                    format!("LIFETIME   RUNS {}   BEST {}   PLAYED {}", lifetime.runs, lifetime.best_score, format_time(total.time)),
+                    format!("COMETS {}   ACCURACY {:.0}%   LONGEST STREAK {}", total.total_destroyed(), total.accuracy() * 100f32, total.longest_streak),
+                ], None, GRAY);
+            }
+            ui.space(10f32);
+            ui.set_style(40, WHITE);
+            if ui.button("PLAY AGAIN") { choice = Some(GameOverChoice::Restart) }
+            if ui.button("MAIN MENU") { choice = Some(GameOverChoice::Quit) }
+            ui.set_style(22, GRAY);
+            ui.label("HOLD R: REWIND");
+        });
+        choice
+    }
+}
+
+impl Scene for GameOverScene {
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (choice, nav) = interact(game, &mut self.focus, |ui| GameOverScene::menu(ui, game));
+        game.set_typing(GameOverScene::names_score(game) && self.focus == 0);
+        match choice {
+            Some(GameOverChoice::Name(name)) => {
+                game.set_player_name(name);
+                Transition::None
+            }
+            Some(GameOverChoice::Restart) => {
+                game.start_run();
+                Transition::Pop
+            }
+            _ if nav.back => Transition::Reset(Box::new(TitleScene::new())),
+            Some(GameOverChoice::Quit) => Transition::Reset(Box::new(TitleScene::new())),
+            None => Transition::None,
+        }
+    }
+    fn exit(&mut self, game: &mut GameState) {
+        game.set_typing(false);
+    }
+    fn update(&mut self, game: &mut GameState) -> Transition {
+        match game.get_phase() {
+            GamePhase::END => Transition::None,
+            _ => Transition::Pop,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        dim(game.get_view_size(), 0.85f32);
+        GameOverScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus), game);
+    }
+    fn is_overlay(&self) -> bool { true }
+}
+
+// settings that are picked from a few values, the numbers are sliders
+#[derive(Clone, Copy)]
+enum Setting {
+    FlightModel,
+    ScaleMode,
+    TimeScale,
+    Debug,
+}
+
+impl Setting {
+    fn get_name(&self) -> &'static str {
+        match self {
+            Setting::FlightModel => "FLIGHT MODEL",
+            Setting::ScaleMode => "SCALING",
+            Setting::TimeScale => "TIME SCALE",
+            Setting::Debug => "DEBUG OVERLAY",
+        }
+    }
+    fn get_value(&self, game: &GameState) -> String {
+        match self {
+            Setting::FlightModel => game.get_flight_model().get_name().to_uppercase(),
+            Setting::ScaleMode => game.get_scale_mode().get_name().to_uppercase(),
+            Setting::TimeScale => format!("x{}", game.get_time_scale()),
+            Setting::Debug => String::from(if game.is_debug_enabled() { "ON" } else { "OFF" }),
+        }
+    }
+    // step is -1 or 1, settings without an order just cycle
+    fn change(&self, game: &mut GameState, step: i32) {
+        match self {
+            Setting::FlightModel => game.set_flight_model(game.get_flight_model().next()),
+            Setting::ScaleMode => game.set_scale_mode(game.get_scale_mode().next()),
+            Setting::TimeScale => game.set_time_scale(game.get_time_scale() * 2f32.powi(step)),
+            Setting::Debug => game.toggle_debug(),
+        }
+    }
+}
+
+enum SettingsEdit {
+    Step(Setting, i32),
+    Wells(u32),
+    Rewind(f32),
+    Back,
+}
+
+// reachable from the title and the pause menu, changes apply at once
+#[derive(Default)]
+pub struct SettingsScene {
+    focus: usize,
+}
+
+impl SettingsScene {
+    pub fn new() -> SettingsScene { SettingsScene::default() }
+
+    fn menu(ui: &mut Ui, game: &GameState) -> Option<SettingsEdit> {
+        let mut edit = None;
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 90f32), 24f32, |ui| {
+            ui.set_style(50, WHITE);
+            ui.label("SETTINGS");
+            ui.set_style(DEFAULT_SIZE, WHITE);
+            for setting in [Setting::FlightModel, Setting::ScaleMode, Setting::TimeScale] {
+                match ui.choice(setting.get_name(), &setting.get_value(game)) {
+                    0 => (),
+                    step => edit = Some(SettingsEdit::Step(setting, step)),
+                }
+            }
+            let wells = game.get_random_wells() as f32;
+            let changed = ui.slider("GRAVITY WELLS", wells, 0f32, MAX_WELLS, 1f32);
+            if changed != wells { edit = Some(SettingsEdit::Wells(changed as u32)) }
+            let rewind = game.get_rewind_seconds();
+            let changed = ui.slider("REWIND SECONDS", rewind, 0f32, MAX_REWIND, REWIND_STEP);
+            if changed != rewind { edit = Some(SettingsEdit::Rewind(changed)) }
+            match ui.choice(Setting::Debug.get_name(), &Setting::Debug.get_value(game)) {
+                0 => (),
+                step => edit = Some(SettingsEdit::Step(Setting::Debug, step)),
+            }
+            ui.space(10f32);
+            if ui.button("BACK") { edit = Some(SettingsEdit::Back) }
+            ui.set_style(24, GRAY);
+            ui.label("UP / DOWN: CHOOSE    LEFT / RIGHT: CHANGE    ESC: BACK");
+        });
+        edit
+    }
+}
+
+impl Scene for SettingsScene {
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (edit, nav) = interact(game, &mut self.focus, |ui| SettingsScene::menu(ui, game));
+        match edit {
+            _ if nav.back => return Transition::Pop,
+            Some(SettingsEdit::Back) => return Transition::Pop,
+            Some(SettingsEdit::Step(setting, step)) => setting.change(game, step),
+            Some(SettingsEdit::Wells(count)) => game.set_random_wells(count),
+            Some(SettingsEdit::Rewind(seconds)) => game.set_rewind_seconds(seconds),
+            None => (),
+        }
+        Transition::None
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        SettingsScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus), game);
+    }
+}
+
+#[derive(Default)]
+pub struct HighScoresScene {
+    focus: usize,
+}
+
+impl HighScoresScene {
+    pub fn new() -> HighScoresScene { HighScoresScene::default() }
+
+    // true when done
+    fn menu(ui: &mut Ui, game: &GameState) -> bool {
+        let high_scores = game.get_high_scores();
+        let rows: Vec<String> = high_scores.get_entries().iter().enumerate()
+            .map(|(idx, entry)| {
+                let name = if entry.name.is_empty() { "-" } else { entry.name.as_str() };
+                format!("{}.   {}   {}   {}   {}", idx + 1, entry.score, name, entry.ship.to_uppercase(), format_time(entry.time))
+            })
+            .collect();
+        let mut back = false;
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 70f32), 24f32, |ui| {
+            ui.set_style(50, WHITE);
+            ui.label("HIGH SCORES");
+            ui.set_style(28, WHITE);
+            match rows.is_empty() {
+                true => ui.label("NO RUNS YET"),
+                false => ui.list(&rows, Some(0), YELLOW),
+            }
+            ui.set_style(24, WHITE);
+            back = ui.button("BACK");
+        });
+        back
+    }
+}
+
+impl Scene for HighScoresScene {
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (back, nav) = interact(game, &mut self.focus, |ui| HighScoresScene::menu(ui, game));
+        match back || nav.back {
+            true => Transition::Pop,
+            false => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        HighScoresScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus), game);
+    }
+}
+
+#[derive(Default)]
+pub struct AchievementsScene {
+    focus: usize,
+}
+
+impl AchievementsScene {
+    pub fn new() -> AchievementsScene { AchievementsScene::default() }
+
+    // true when done
+    fn menu(ui: &mut Ui, game: &GameState) -> bool {
+        let tracker = game.get_achievements();
+        let achievements = tracker.get_achievements();
+        ui.anchored(Anchor::Top, Vec2::new(0f32, 50f32), 0f32, |ui| {
+            ui.set_style(50, WHITE);
+            ui.label(&format!("ACHIEVEMENTS  {} / {}", tracker.unlocked_count(), achievements.len()));
+        });
+        // titles on the left, how far along they are on the right
+        let row_height = (520f32 / achievements.len().max(1) as f32).min(60f32);
+        for (idx, achievement) in achievements.iter().enumerate() {
+            let y = 150f32 + idx as f32 * row_height;
+            let (color, status) = match tracker.is_unlocked(idx) {
+                true => (YELLOW, "UNLOCKED".to_string()),
+                false if achievement.is_per_run() => (GRAY, "IN ONE RUN".to_string()),
+                false => (GRAY, format!("{:.0} / {:.0}", tracker.get_progress(idx), achievement.target)),
+            };
+            ui.anchored(Anchor::TopLeft, Vec2::new(160f32, y), 4f32, |ui| {
+                ui.set_style(26, color);
+                ui.label(&achievement.title);
+                ui.set_style(18, GRAY);
+                ui.label(&achievement.text);
+            });
+            ui.anchored(Anchor::TopRight, Vec2::new(-160f32, y + 12f32), 0f32, |ui| {
+                ui.set_style(22, color);
+                ui.label(&status);
+            });
+        }
+        let mut back = false;
+        ui.anchored(Anchor::Bottom, Vec2::new(0f32, -30f32), 0f32, |ui| {
+            ui.set_style(24, WHITE);
+            back = ui.button("BACK");
+        });
+        back
+    }
+}
+
+impl Scene for AchievementsScene {
+    fn frame_inputs(&mut self, game: &mut GameState, _delta_time: f32) -> Transition {
+        let (back, nav) = interact(game, &mut self.focus, |ui| AchievementsScene::menu(ui, game));
+        match back || nav.back {
+            true => Transition::Pop,
+            false => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        AchievementsScene::menu(&mut Ui::new(font, game.get_view_size(), self.focus), game);
+    }
+}
+
+// picks up the custom level that was played last, if any
+pub struct EditorScene {
+    editor: Editor,
+}
+
+impl EditorScene {
+    pub fn new(game: &GameState) -> EditorScene {
+        let editor = match game.get_custom_level() {
+            Some(layout) => Editor::with_layout(layout.clone()),
+            None => Editor::new(game.get_arena()),
+        };
+        EditorScene { editor }
+    }
+}
+
+impl Scene for EditorScene {
+    // called once per rendered frame, edge triggered keys would get lost at physics rate
+    fn frame_inputs(&mut self, game: &mut GameState, delta_time: f32) -> Transition {
+        match self.editor.inputs(delta_time, game.mouse_position()) {
+            EditorAction::Play(layout) => {
+                game.set_custom_level(Some(layout));
+                Transition::Replace(Box::new(PlayScene))
+            }
+            EditorAction::Exit => Transition::Pop,
+            EditorAction::None => Transition::None,
+        }
+    }
+    fn draw(&self, game: &GameState, font: &Font, _alpha: f32) {
+        self.editor.draw(&mut Ui::new(font, game.get_view_size(), 0));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use macroquad::math::Vec2;
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, FLOAT, INT};
+use crate::comet::Size;
+
+// a hook that runs longer than this is stopped and reported like any other script error, so an endless loop
+// cannot hang the game
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+// what a script asked the game to do, applied by GameState after each call into the script;
+// velocities are in pixels per second
+#[derive(Debug, Clone)]
+pub enum ScriptCommand {
+    SpawnComet { size: Size, pos: Option<Vec2>, vel: Option<Vec2> },
+    Message { text: String, seconds: f32 },
+    SpawnWell { pos: Vec2, strength: f32, horizon: f32 },
+    ClearWells,
+}
+
+// read-only numbers the script can query, refreshed before every call
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScriptView {
+    pub score: i32,
+    pub lives: u32,
+    pub game_duration: f32,
+    pub comet_count: usize,
+    pub arena: Vec2,
+}
+
+// a level script with the hooks on_start(), on_tick() and on_timer(name), all optional;
+// state that has to survive between calls lives in `this`, which is reset on every start
+pub struct LevelScript {
+    engine: Engine,
+    ast: AST,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    this: Dynamic,
+    timers: Vec<(String, f32)>,
+    fired_timers: Vec<String>,
+    commands: Arc<Mutex<Vec<ScriptCommand>>>,
+    new_timers: Arc<Mutex<Vec<(String, f32)>>>,
+    view: Arc<Mutex<ScriptView>>,
+}
+
+impl LevelScript {
+    pub fn load(path: impl AsRef<Path>) -> Result<LevelScript, String> {
+        let path = path.as_ref().to_path_buf();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let new_timers = Arc::new(Mutex::new(Vec::new()));
+        let view = Arc::new(Mutex::new(ScriptView::default()));
+        let engine = LevelScript::build_engine(&commands, &new_timers, &view);
+        let ast = engine.compile_file(path.clone()).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Ok(LevelScript {
+            engine,
+            ast,
+            modified: LevelScript::modified_time(&path),
+            path,
+            this: Dynamic::from_map(Map::new()),
+            timers: Vec::new(),
+            fired_timers: Vec::new(),
+            commands,
+            new_timers,
+            view,
+        })
+    }
+
+    fn build_engine(commands: &Arc<Mutex<Vec<ScriptCommand>>>,
+                    new_timers: &Arc<Mutex<Vec<(String, f32)>>>,
+                    view: &Arc<Mutex<ScriptView>>) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let queue = commands.clone();
+        engine.register_fn("spawn_comet", move |size: INT| {
+            if let Some(size) = Size::from_level(size) {
+                queue.lock().unwrap().push(ScriptCommand::SpawnComet { size, pos: None, vel: None });
+            }
+        });
+        let queue = commands.clone();
+        engine.register_fn("spawn_comet", move |size: INT, x: FLOAT, y: FLOAT, vx: FLOAT, vy: FLOAT| {
+            if let Some(size) = Size::from_level(size) {
+                queue.lock().unwrap().push(ScriptCommand::SpawnComet {
+                    size,
+                    pos: Some(Vec2::new(x, y)),
+                    vel: Some(Vec2::new(vx, vy)),
+                });
+            }
+        });
+        let queue = commands.clone();
+        engine.register_fn("spawn_well", move |x: FLOAT, y: FLOAT, strength: FLOAT, horizon: FLOAT| {
+            queue.lock().unwrap().push(ScriptCommand::SpawnWell { pos: Vec2::new(x, y), strength, horizon });
+        });
+        let queue = commands.clone();
+        engine.register_fn("clear_wells", move || queue.lock().unwrap().push(ScriptCommand::ClearWells));
+        let queue = commands.clone();
+        engine.register_fn("message", move |text: &str, seconds: FLOAT| {
+            queue.lock().unwrap().push(ScriptCommand::Message { text: text.to_string(), seconds });
+        });
+        let timers = new_timers.clone();
+        engine.register_fn("set_timer", move |name: &str, seconds: FLOAT| {
+            timers.lock().unwrap().push((name.to_string(), seconds));
+        });
+
+        let state = view.clone();
+        engine.register_fn("score", move || state.lock().unwrap().score as INT);
+        let state = view.clone();
+        engine.register_fn("lives", move || state.lock().unwrap().lives as INT);
+        let state = view.clone();
+        engine.register_fn("game_duration", move || state.lock().unwrap().game_duration);
+        let state = view.clone();
+        engine.register_fn("comet_count", move || state.lock().unwrap().comet_count as INT);
+        let state = view.clone();
+        engine.register_fn("arena_width", move || state.lock().unwrap().arena.x);
+        let state = view.clone();
+        engine.register_fn("arena_height", move || state.lock().unwrap().arena.y);
+        engine
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+
+    pub fn get_path(&self) -> &Path { &self.path }
+
+    // recompiles the file if it changed on disk; a broken edit keeps the old version running
+    pub fn reload_if_changed(&mut self) -> bool {
+        let modified = LevelScript::modified_time(&self.path);
+        if modified == self.modified { return false }
+        self.modified = modified;
+        match self.engine.compile_file(self.path.clone()) {
+            Ok(ast) => {
+                self.ast = ast;
+                true
+            }
+            Err(err) => {
+                eprintln!("{}: {}", self.path.display(), err);
+                false
+            }
+        }
+    }
+
+    pub fn start(&mut self, view: ScriptView) -> Vec<ScriptCommand> {
+        self.this = Dynamic::from_map(Map::new());
+        self.timers.clear();
+        self.fired_timers.clear();
+        self.call(view, "on_start", ());
+        self.take_commands()
+    }
+
+    // counts timers down in seconds, the ones that ran out are reported on the next tick
+    pub fn advance_timers(&mut self, delta_time: f32) {
+        for (name, remaining) in self.timers.iter_mut() {
+            *remaining -= delta_time;
+            if *remaining <= 0f32 { self.fired_timers.push(name.clone()) }
+        }
+        self.timers.retain(|(_, remaining)| *remaining > 0f32);
+    }
+
+    pub fn tick(&mut self, view: ScriptView) -> Vec<ScriptCommand> {
+        for name in std::mem::take(&mut self.fired_timers) {
+            self.call(view, "on_timer", (name,));
+        }
+        self.call(view, "on_tick", ());
+        self.take_commands()
+    }
+
+    fn call(&mut self, view: ScriptView, name: &str, args: impl rhai::FuncArgs) {
+        if !self.ast.iter_functions().any(|function| function.name == name) { return }
+        *self.view.lock().unwrap() = view;
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.this);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, args);
+        if let Err(err) = result {
+            eprintln!("{} in {}: {}", self.path.display(), name, err);
+        }
+        self.timers.append(&mut self.new_timers.lock().unwrap());
+    }
+
+    fn take_commands(&mut self) -> Vec<ScriptCommand> {
+        std::mem::take(&mut *self.commands.lock().unwrap())
+    }
+}
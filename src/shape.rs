@@ -1,26 +1,49 @@
 use std::f32::consts::PI;
-use macroquad::color::WHITE;
+use macroquad::color::{Color, WHITE};
 use macroquad::math::Vec2;
 use macroquad::shapes::draw_line;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
+// closed outline around pos, vertices are relative to pos before rotation
+#[derive(Clone)]
 pub struct Shape {
     pos: Vec2,
     rotation: f32,
-    sides: u32,
-    radius: f32,
+    vertices: Vec<Vec2>,
+    radius: f32,    // distance of the farthest vertex, for cheap rejection
     line_thickness: f32,
 }
 
 impl Shape {
+    // regular polygon with all corners on radius
     pub fn new(pos: Vec2, sides: u32, radius: f32, line_thickness: f32) -> Shape {
+        Shape::from_vertices(pos, regular_polygon(sides, radius), line_thickness)
+    }
+    pub fn from_vertices(pos: Vec2, vertices: Vec<Vec2>, line_thickness: f32) -> Shape {
         Shape {
             pos,
             rotation: 0f32,
-            sides,
-            radius,
+            radius: bounding_radius(&vertices),
+            vertices,
             line_thickness,
         }
     }
+    // jagged rock outline, the same seed always gives the same rock; roughness 0 is a regular polygon,
+    // 1 lets single vertices sink all the way to the centre
+    pub fn asteroid(pos: Vec2, sides: u32, radius: f32, roughness: f32, seed: u64, line_thickness: f32) -> Shape {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let sides = sides.max(3);
+        let step_size = 2f32 * PI / sides as f32;
+        let roughness = roughness.clamp(0f32, 1f32);
+        let vertices = (0..sides).map(|i| {
+            // angles stay in order so the outline never crosses itself
+            let angle = (i as f32 + rng.gen_range(-0.35f32..0.35f32) * roughness) * step_size;
+            let distance = radius * (1f32 - rng.gen_range(0f32..roughness.max(f32::EPSILON)));
+            Vec2::from_angle(angle) * distance
+        }).collect();
+        Shape::from_vertices(pos, vertices, line_thickness)
+    }
     pub fn get_pos(&self) -> Vec2 {
         self.pos
     }
@@ -30,31 +53,256 @@ impl Shape {
     pub fn get_rad(&self) -> f32 {
         self.radius
     }
+    // scales the outline so its farthest vertex ends up on radius
+    pub fn set_rad(&mut self, radius: f32) {
+        if self.radius > 0f32 {
+            let scale = radius / self.radius;
+            self.vertices.iter_mut().for_each(|vertex| *vertex *= scale);
+        }
+        self.radius = radius;
+    }
+    pub fn get_sides(&self) -> u32 {
+        self.vertices.len() as u32
+    }
+    // replaces the outline with a regular polygon of the current radius
+    pub fn set_sides(&mut self, sides: u32) {
+        self.vertices = regular_polygon(sides, self.radius);
+    }
+    pub fn get_vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+    // outline in world coordinates at the current position and rotation
+    pub fn get_world_vertices(&self) -> Vec<Vec2> {
+        self.transformed(self.pos, self.rotation)
+    }
+    pub fn get_rot(&self) -> f32 {
+        self.rotation
+    }
+    pub fn set_rot(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
     pub fn add_pos(&mut self, vec: Vec2) {
         self.pos += vec;
     }
     pub fn add_rot(&mut self, angle: f32) {
         self.rotation += angle;
     }
+    fn transformed(&self, pos: Vec2, rotation: f32) -> Vec<Vec2> {
+        let rotation = Vec2::from_angle(rotation);
+        self.vertices.iter().map(|vertex| pos + rotation.rotate(*vertex)).collect()
+    }
     pub fn draw(&self) {
-        let step_size = 2f32 * PI / self.sides as f32;
-        let mut points: Vec<Vec2> = Vec::new();
-        for i in 0..self.sides {
-            points.push(Vec2::new((self.rotation + i as f32 * step_size).cos(), (self.rotation + i as f32 * step_size).sin()) * self.radius + self.pos)
-        }
-        for i in 0..self.sides as usize {
-            draw_line(points[i].x, points[i].y,
-                      points[(i + 1) % self.sides as usize].x, points[(i + 1) % self.sides as usize].y,
-                      self.line_thickness,
-                      WHITE)
+        self.draw_colored(WHITE);
+    }
+    pub fn draw_colored(&self, color: Color) {
+        self.draw_at(self.pos, self.rotation, color);
+    }
+    // draws the outline somewhere else than where it collides, e.g. interpolated between ticks
+    pub fn draw_at(&self, pos: Vec2, rotation: f32, color: Color) {
+        let points = self.transformed(pos, rotation);
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            draw_line(a.x, a.y, b.x, b.y, self.line_thickness, color);
         }
     }
-    //checks if point is within radius of shape, not precise increasingly for low sides
+    // enclosed area in pixels², independent of position and rotation
+    pub fn area(&self) -> f32 {
+        polygon_area(&self.vertices)
+    }
+    // centre of the enclosed area in world coordinates, pos is only the pivot the outline turns around
+    pub fn centroid(&self) -> Vec2 {
+        self.pos + Vec2::from_angle(self.rotation).rotate(polygon_centroid(&self.vertices))
+    }
+    // the parts of the outline on the side of the line through point that normal points to, each a new shape
+    // pivoting around its own centroid; empty if nothing of the outline lies on that side
+    pub fn clip(&self, point: Vec2, normal: Vec2) -> Vec<Shape> {
+        clip_polygon(&self.get_world_vertices(), point, normal).into_iter()
+            .filter(|piece| piece.len() >= 3 && polygon_area(piece) > f32::EPSILON)
+            .map(|piece| {
+                let centre = polygon_centroid(&piece);
+                let vertices = piece.iter().map(|vertex| *vertex - centre).collect();
+                Shape::from_vertices(centre, vertices, self.line_thickness)
+            })
+            .collect()
+    }
+    // cuts the outline along the line through point in direction, into the pieces on either side of it
+    pub fn cut(&self, point: Vec2, direction: Vec2) -> Vec<Shape> {
+        let normal = direction.perp().normalize_or_zero();
+        [normal, -normal].into_iter().flat_map(|normal| self.clip(point, normal)).collect()
+    }
+    // exact test against the outline, the bounding radius rules out most points first
     pub fn contains(&self, point: Vec2) -> bool {
-        let distance = (self.pos - point).length();
-        if distance < self.radius {
-            return true
+        if (self.pos - point).length() > self.radius { return false }
+        polygon_contains(&self.get_world_vertices(), point)
+    }
+}
+
+fn regular_polygon(sides: u32, radius: f32) -> Vec<Vec2> {
+    let step_size = 2f32 * PI / sides.max(3) as f32;
+    (0..sides.max(3)).map(|i| Vec2::from_angle(i as f32 * step_size) * radius).collect()
+}
+
+fn bounding_radius(vertices: &[Vec2]) -> f32 {
+    vertices.iter().map(|vertex| vertex.length()).fold(0f32, f32::max)
+}
+
+// shoelace formula, the outline may wind either way
+pub fn polygon_area(polygon: &[Vec2]) -> f32 {
+    (signed_area(polygon) / 2f32).abs()
+}
+
+// centre of the enclosed area, falls back to the average vertex for degenerate outlines
+pub fn polygon_centroid(polygon: &[Vec2]) -> Vec2 {
+    let twice_area = signed_area(polygon);
+    if twice_area.abs() <= f32::EPSILON {
+        return polygon.iter().fold(Vec2::ZERO, |sum, vertex| sum + *vertex) / polygon.len().max(1) as f32;
+    }
+    let weighted = polygon.iter().enumerate().fold(Vec2::ZERO, |sum, (idx, a)| {
+        let b = polygon[(idx + 1) % polygon.len()];
+        sum + (*a + b) * a.perp_dot(b)
+    });
+    weighted / (3f32 * twice_area)
+}
+
+fn signed_area(polygon: &[Vec2]) -> f32 {
+    polygon.iter().enumerate().map(|(idx, a)| a.perp_dot(polygon[(idx + 1) % polygon.len()])).sum()
+}
+
+// Sutherland-Hodgman against a single half plane, keeps what lies on the side normal points to;
+// a concave outline can fall apart into several parts there, each comes back as its own outline
+pub fn clip_polygon(polygon: &[Vec2], point: Vec2, normal: Vec2) -> Vec<Vec<Vec2>> {
+    let side = |vertex: Vec2| (vertex - point).dot(normal);
+    // the outline on the kept side, every crossing of the line marked as leaving (true) or entering it
+    let mut clipped: Vec<(Vec2, Option<bool>)> = Vec::new();
+    for (idx, a) in polygon.iter().enumerate() {
+        let b = polygon[(idx + 1) % polygon.len()];
+        let (side_a, side_b) = (side(*a), side(b));
+        if side_a >= 0f32 {
+            clipped.push((*a, None));
+        }
+        if (side_a >= 0f32) != (side_b >= 0f32) {
+            clipped.push((a.lerp(b, side_a / (side_a - side_b)), Some(side_a >= 0f32)));
+        }
+    }
+    // along the line the crossings alternate between entering and leaving the outline, so neighbours pair up
+    // into the stretches of line that lie inside it; leaving at one end continues at the other
+    let mut crossings: Vec<usize> = (0..clipped.len()).filter(|idx| clipped[*idx].1.is_some()).collect();
+    crossings.sort_by(|a, b| clipped[*a].0.perp_dot(normal).total_cmp(&clipped[*b].0.perp_dot(normal)));
+    let mut partner: Vec<Option<usize>> = vec![None; clipped.len()];
+    for pair in crossings.chunks_exact(2) {
+        partner[pair[0]] = Some(pair[1]);
+        partner[pair[1]] = Some(pair[0]);
+    }
+    let mut visited = vec![false; clipped.len()];
+    let mut parts = Vec::new();
+    for start in 0..clipped.len() {
+        let mut part = Vec::new();
+        let mut idx = start;
+        while !visited[idx] {
+            visited[idx] = true;
+            part.push(clipped[idx].0);
+            idx = match (clipped[idx].1, partner[idx]) {
+                (Some(true), Some(other)) => other,
+                _ => (idx + 1) % clipped.len(),
+            };
         }
-        false
+        if !part.is_empty() { parts.push(part) }
+    }
+    parts
+}
+
+// even-odd rule, works for concave outlines too
+pub fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (idx, a) in polygon.iter().enumerate() {
+        let b = polygon[(idx + 1) % polygon.len()];
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+pub fn circle_overlaps_polygon(centre: Vec2, radius: f32, polygon: &[Vec2]) -> bool {
+    if polygon_contains(polygon, centre) { return true }
+    polygon.iter().enumerate().any(|(idx, a)| {
+        let b = polygon[(idx + 1) % polygon.len()];
+        let t = ((centre - *a).dot(b - *a) / (b - *a).length_squared()).clamp(0f32, 1f32);
+        (*a + (b - *a) * t).distance(centre) < radius
+    })
+}
+
+// true if the outlines cross or one lies inside the other
+pub fn polygons_overlap(a: &[Vec2], b: &[Vec2]) -> bool {
+    let edges = |polygon: &[Vec2]| -> Vec<(Vec2, Vec2)> {
+        polygon.iter().enumerate().map(|(idx, p)| (*p, polygon[(idx + 1) % polygon.len()])).collect()
+    };
+    let crosses = |(p1, p2): (Vec2, Vec2), (q1, q2): (Vec2, Vec2)| {
+        let side = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp_dot(c - a);
+        side(p1, p2, q1) * side(p1, p2, q2) < 0f32 && side(q1, q2, p1) * side(q1, q2, p2) < 0f32
+    };
+    let (edges_a, edges_b) = (edges(a), edges(b));
+    edges_a.iter().any(|edge_a| edges_b.iter().any(|edge_b| crosses(*edge_a, *edge_b)))
+        || a.first().is_some_and(|point| polygon_contains(b, *point))
+        || b.first().is_some_and(|point| polygon_contains(a, *point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(pos: Vec2, half: f32) -> Shape {
+        let vertices = vec![Vec2::new(-half, -half), Vec2::new(half, -half), Vec2::new(half, half), Vec2::new(-half, half)];
+        Shape::from_vertices(pos, vertices, 1f32)
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 0.001f32
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn square_cut_in_half() {
+        let shape = square(Vec2::new(100f32, 50f32), 10f32);
+        let mut pieces = shape.cut(Vec2::new(100f32, 50f32), Vec2::Y);
+        assert_eq!(pieces.len(), 2);
+        pieces.sort_by(|a, b| a.get_pos().x.total_cmp(&b.get_pos().x));
+        assert!((pieces[0].area() + pieces[1].area() - shape.area()).abs() < 0.01f32);
+        assert!((pieces[0].area() - 200f32).abs() < 0.01f32);
+        assert!(close(pieces[0].centroid(), Vec2::new(95f32, 50f32)));
+        assert!(close(pieces[1].centroid(), Vec2::new(105f32, 50f32)));
+        // pieces pivot around their own centroid
+        assert!(close(pieces[0].get_pos(), pieces[0].centroid()));
+    }
+
+    #[test]
+    fn line_that_misses_leaves_one_piece() {
+        let shape = square(Vec2::new(100f32, 50f32), 10f32);
+        let pieces = shape.cut(Vec2::new(200f32, 0f32), Vec2::Y);
+        assert_eq!(pieces.len(), 1);
+        assert!((pieces[0].area() - shape.area()).abs() < 0.01f32);
+        assert!(close(pieces[0].centroid(), shape.centroid()));
+    }
+
+    #[test]
+    fn concave_outline_cut_into_several_parts() {
+        // a U, the cut runs across both arms
+        let vertices = vec![
+            Vec2::new(0f32, 0f32), Vec2::new(30f32, 0f32), Vec2::new(30f32, 30f32), Vec2::new(20f32, 30f32),
+            Vec2::new(20f32, 10f32), Vec2::new(10f32, 10f32), Vec2::new(10f32, 30f32), Vec2::new(0f32, 30f32),
+        ];
+        let shape = Shape::from_vertices(Vec2::ZERO, vertices, 1f32);
+        let mut pieces = shape.cut(Vec2::new(0f32, 20f32), Vec2::X);
+        assert_eq!(pieces.len(), 3);
+        pieces.sort_by(|a, b| (a.area(), a.centroid().x).partial_cmp(&(b.area(), b.centroid().x)).unwrap());
+        // each arm tip is a piece of its own
+        assert!((pieces[0].area() - 100f32).abs() < 0.01f32);
+        assert!((pieces[1].area() - 100f32).abs() < 0.01f32);
+        assert!(close(pieces[0].centroid(), Vec2::new(5f32, 25f32)));
+        assert!(close(pieces[1].centroid(), Vec2::new(25f32, 25f32)));
+        assert!(!pieces[0].contains(Vec2::new(15f32, 25f32)));
+        assert!(pieces[..2].iter().all(|piece| piece.get_sides() == 4));
+        assert!((pieces[2].area() - 500f32).abs() < 0.01f32);
+        assert!(pieces[2].contains(Vec2::new(15f32, 5f32)));
+        assert!(!pieces[2].contains(Vec2::new(15f32, 15f32)));
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use macroquad::math::Vec2;
+
+pub const SHIPS_PATH: &str = "res/ships.txt";
+
+// compiled in, so headless runs and a missing res folder still have ships
+const BUILTIN_SHIPS: &str = include_str!("../res/ships.txt");
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitShape {
+    Circle(f32),
+    Hull,
+}
+
+// one barrel, fires along the nose turned by angle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gun {
+    pub offset: Vec2,
+    pub angle: f32,
+}
+
+// stats and looks of a selectable ship, in ship space x points forward and y to the right of the nose
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipClass {
+    pub name: String,
+    pub hull: Vec<Vec2>,
+    pub hit: HitShape,
+    pub thrust: f32,
+    pub max_speed: f32,
+    pub rotation_rate: f32,
+    pub lives: u32,
+    pub cooldown: f32,
+    pub projectile_speed: f32,
+    pub guns: Vec<Gun>,
+}
+
+impl ShipClass {
+    fn new(name: &str) -> ShipClass {
+        ShipClass {
+            name: name.to_string(),
+            hull: Vec::new(),
+            hit: HitShape::Hull,
+            thrust: 900f32,
+            max_speed: 300f32,
+            rotation_rate: 4.5f32,
+            lives: 3,
+            cooldown: 0.2f32,
+            projectile_speed: 900f32,
+            guns: Vec::new(),
+        }
+    }
+    // farthest point of the hit shape from the centre, for checks that only know circles
+    pub fn get_hit_radius(&self) -> f32 {
+        match self.hit {
+            HitShape::Circle(radius) => radius,
+            HitShape::Hull => self.hull.iter().map(|point| point.length()).fold(0f32, f32::max),
+        }
+    }
+
+    pub fn builtin() -> &'static [ShipClass] {
+        static SHIPS: OnceLock<Vec<ShipClass>> = OnceLock::new();
+        SHIPS.get_or_init(|| ShipClass::parse(BUILTIN_SHIPS).expect("built in ship classes are valid"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<ShipClass>, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        ShipClass::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(text: &str) -> Result<Vec<ShipClass>, String> {
+        let mut ships: Vec<ShipClass> = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+            let mut words = line.split_whitespace();
+            let key = words.next().unwrap_or_default();
+            let result = match (key, ships.last_mut()) {
+                ("ship", _) => words.next().map(|name| ships.push(ShipClass::new(name))).ok_or("missing ship name".to_string()),
+                (_, None) => Err(format!("'{}' before the first ship", key)),
+                (_, Some(ship)) => parse_stat(ship, key, &mut words),
+            };
+            result.map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        for ship in ships.iter() {
+            if ship.hull.len() < 3 { return Err(format!("ship {} needs a hull of at least 3 points", ship.name)) }
+            if ship.guns.is_empty() { return Err(format!("ship {} has no guns", ship.name)) }
+        }
+        if ships.is_empty() { return Err("no ships defined".to_string()) }
+        Ok(ships)
+    }
+}
+
+fn parse_f32<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<f32, String> {
+    let word = words.next().ok_or("missing number")?;
+    word.parse().map_err(|_| format!("'{}' is not a number", word))
+}
+
+fn parse_stat<'a>(ship: &mut ShipClass, key: &str, words: &mut impl Iterator<Item = &'a str>) -> Result<(), String> {
+    match key {
+        "hull" => {
+            let numbers = words.map(|word| word.parse::<f32>().map_err(|_| format!("'{}' is not a number", word)))
+                .collect::<Result<Vec<f32>, String>>()?;
+            if numbers.len() % 2 != 0 { return Err("hull needs pairs of numbers".to_string()) }
+            ship.hull = numbers.chunks(2).map(|pair| Vec2::new(pair[0], pair[1])).collect();
+        }
+        "hit" => match words.next() {
+            Some("circle") => ship.hit = HitShape::Circle(parse_f32(words)?),
+            Some("hull") => ship.hit = HitShape::Hull,
+            _ => return Err("hit is either 'circle r' or 'hull'".to_string()),
+        },
+        "thrust" => ship.thrust = parse_f32(words)?,
+        "max_speed" => ship.max_speed = parse_f32(words)?,
+        "rotation_rate" => ship.rotation_rate = parse_f32(words)?,
+        "lives" => ship.lives = (parse_f32(words)? as u32).max(1),
+        "cooldown" => ship.cooldown = parse_f32(words)?,
+        "projectile_speed" => ship.projectile_speed = parse_f32(words)?,
+        "gun" => ship.guns.push(Gun { offset: Vec2::new(parse_f32(words)?, parse_f32(words)?), angle: parse_f32(words)? }),
+        _ => return Err(format!("unknown ship key '{}'", key)),
+    }
+    Ok(())
+}
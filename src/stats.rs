@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::Path;
+use crate::comet::Size;
+use crate::event::{Cause, GameEvent, Subscriber};
+
+pub const STATS_PATH: &str = "res/stats.txt";
+
+// what happened during a single run, from the start to the game over
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunStats {
+    pub shots: u32,
+    pub hits: u32,
+    pub destroyed: [u32; 3],    // comets by size, smallest first
+    pub distance: f32,          // pixels
+    pub time: f32,              // seconds
+    pub lives_lost: u32,
+    pub longest_streak: u32,    // hits in a row without a miss
+    pub streak: u32,            // the current one
+}
+
+impl RunStats {
+    pub fn accuracy(&self) -> f32 {
+        match self.shots {
+            0 => 0f32,
+            shots => self.hits.min(shots) as f32 / shots as f32,
+        }
+    }
+    pub fn get_destroyed(&self, size: &Size) -> u32 {
+        self.destroyed[size.get_level() as usize - 1]
+    }
+    pub fn record_destroyed(&mut self, size: &Size) {
+        self.destroyed[size.get_level() as usize - 1] += 1;
+    }
+    pub fn total_destroyed(&self) -> u32 {
+        self.destroyed.iter().sum()
+    }
+    fn hit(&mut self) {
+        self.hits += 1;
+        self.streak += 1;
+        self.longest_streak = self.longest_streak.max(self.streak);
+    }
+}
+
+impl Subscriber for RunStats {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Tick { delta_time, distance } => {
+                self.time += delta_time;
+                self.distance += distance;
+            }
+            GameEvent::ProjectileFired { .. } => self.shots += 1,
+            GameEvent::ProjectileMissed { .. } => self.streak = 0,
+            GameEvent::CometDestroyed { size, by: Cause::Projectile, .. } => {
+                self.hit();
+                self.record_destroyed(size);
+            }
+            GameEvent::PlayerHit { life_lost: true, .. } => self.lives_lost += 1,
+            _ => (),
+        }
+    }
+}
+
+// every finished run added up, kept on disk between sessions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LifetimeStats {
+    pub runs: u32,
+    pub best_score: i32,
+    pub total: RunStats,    // sums, except for the longest streak which is the best of all runs
+}
+
+impl LifetimeStats {
+    pub fn with_run(&self, run: &RunStats, score: i32) -> LifetimeStats {
+        let total = &self.total;
+        LifetimeStats {
+            runs: self.runs + 1,
+            best_score: if self.runs == 0 { score } else { self.best_score.max(score) },
+            total: RunStats {
+                shots: total.shots + run.shots,
+                hits: total.hits + run.hits,
+                destroyed: [0, 1, 2].map(|idx| total.destroyed[idx] + run.destroyed[idx]),
+                distance: total.distance + run.distance,
+                time: total.time + run.time,
+                lives_lost: total.lives_lost + run.lives_lost,
+                longest_streak: total.longest_streak.max(run.longest_streak),
+                streak: 0,
+            },
+        }
+    }
+
+    // a missing file is a player who has not finished a run yet
+    pub fn load(path: impl AsRef<Path>) -> Result<LifetimeStats, String> {
+        let path = path.as_ref();
+        if !path.exists() { return Ok(LifetimeStats::default()) }
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        LifetimeStats::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        }
+        fs::write(path, self.to_text()).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn to_text(&self) -> String {
+        let total = &self.total;
+        let mut text = String::from("# asteroids lifetime stats\n");
+        text += &format!("runs {}\n", self.runs);
+        text += &format!("best_score {}\n", self.best_score);
+        text += &format!("shots {}\n", total.shots);
+        text += &format!("hits {}\n", total.hits);
+        text += &format!("destroyed {} {} {}\n", total.destroyed[0], total.destroyed[1], total.destroyed[2]);
+        text += &format!("distance {}\n", total.distance);
+        text += &format!("time {}\n", total.time);
+        text += &format!("lives_lost {}\n", total.lives_lost);
+        text += &format!("longest_streak {}\n", total.longest_streak);
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<LifetimeStats, String> {
+        let mut stats = LifetimeStats::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+            let mut words = line.split_whitespace();
+            let total = &mut stats.total;
+            let result = match words.next() {
+                Some("runs") => parse(&mut words).map(|runs| stats.runs = runs),
+                Some("best_score") => parse(&mut words).map(|score| stats.best_score = score),
+                Some("shots") => parse(&mut words).map(|shots| total.shots = shots),
+                Some("hits") => parse(&mut words).map(|hits| total.hits = hits),
+                Some("destroyed") => (0..3).try_for_each(|idx| parse(&mut words).map(|count| total.destroyed[idx] = count)),
+                Some("distance") => parse(&mut words).map(|distance| total.distance = distance),
+                Some("time") => parse(&mut words).map(|time| total.time = time),
+                Some("lives_lost") => parse(&mut words).map(|lives| total.lives_lost = lives),
+                Some("longest_streak") => parse(&mut words).map(|streak| total.longest_streak = streak),
+                Some(word) => Err(format!("unknown entry '{}'", word)),
+                None => Ok(()),
+            };
+            result.map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        Ok(stats)
+    }
+}
+
+fn parse<'a, T: std::str::FromStr>(words: &mut impl Iterator<Item = &'a str>) -> Result<T, String> {
+    let word = words.next().ok_or("missing number")?;
+    word.parse().map_err(|_| format!("'{}' is not a number", word))
+}
+
+// minutes and seconds, hours once there are any
+pub fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0f32) as u32;
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
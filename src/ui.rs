@@ -0,0 +1,350 @@
+use std::ops::Range;
+use macroquad::color::{Color, GRAY, WHITE};
+use macroquad::input::is_key_pressed;
+use macroquad::input::KeyCode::{Backspace, Down, Enter, Escape, Left, Right, Up};
+use macroquad::math::{Rect, Vec2};
+use macroquad::prelude::{draw_rectangle, draw_rectangle_lines, draw_text_ex, TextParams};
+use macroquad::text::{Font, measure_text};
+use crate::gamepad::{Gamepads, PadButton};
+
+pub const DEFAULT_SIZE: u16 = 30;
+
+const BASELINE: f32 = 0.8f32;      // of the font size, from the top of a line
+const SLIDER_WIDTH: f32 = 200f32;
+const FOCUS_COLOR: Color = WHITE;
+const IDLE_COLOR: Color = GRAY;
+
+// where a panel sits in the view, it grows away from the edges it is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // 0, 0.5 or 1 of the view along each axis
+    fn factor(&self) -> Vec2 {
+        let (x, y) = match self {
+            Anchor::TopLeft => (0f32, 0f32),
+            Anchor::Top => (0.5f32, 0f32),
+            Anchor::TopRight => (1f32, 0f32),
+            Anchor::Left => (0f32, 0.5f32),
+            Anchor::Centre => (0.5f32, 0.5f32),
+            Anchor::Right => (1f32, 0.5f32),
+            Anchor::BottomLeft => (0f32, 1f32),
+            Anchor::Bottom => (0.5f32, 1f32),
+            Anchor::BottomRight => (1f32, 1f32),
+        };
+        Vec2::new(x, y)
+    }
+}
+
+// one frame of menu navigation, from the keyboard and the gamepads
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NavInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub accept: bool,
+    pub back: bool,
+    pub erase: bool,
+    pub typed: Vec<char>,   // for text inputs
+}
+
+impl NavInput {
+    // only the arrows navigate, letters are left to text inputs
+    pub fn from_keyboard(typed: &[char]) -> NavInput {
+        NavInput {
+            up: is_key_pressed(Up),
+            down: is_key_pressed(Down),
+            left: is_key_pressed(Left),
+            right: is_key_pressed(Right),
+            accept: is_key_pressed(Enter),
+            back: is_key_pressed(Escape),
+            erase: is_key_pressed(Backspace),
+            typed: typed.to_vec(),
+        }
+    }
+    // d-pad or stick navigate, south accepts, east goes back and west erases
+    pub fn with_gamepads(mut self, gamepads: &Gamepads) -> NavInput {
+        self.up |= gamepads.is_pressed(PadButton::Up);
+        self.down |= gamepads.is_pressed(PadButton::Down);
+        self.left |= gamepads.is_pressed(PadButton::Left);
+        self.right |= gamepads.is_pressed(PadButton::Right);
+        self.accept |= gamepads.is_pressed(PadButton::South);
+        self.back |= gamepads.is_pressed(PadButton::East);
+        self.erase |= gamepads.is_pressed(PadButton::West);
+        self
+    }
+}
+
+// positions are relative to the item until its container is laid out
+enum Command {
+    Text { text: String, pos: Vec2, size: u16, color: Color },
+    Rect { rect: Rect, color: Color, filled: bool },
+}
+
+struct Item {
+    commands: Range<usize>,
+    size: Vec2,
+}
+
+struct Container {
+    horizontal: bool,
+    spacing: f32,
+    items: Vec<Item>,
+}
+
+// immediate mode: the same code describes a screen twice per frame, once to react to the inputs and once to draw it,
+// the only state kept in between is which focusable widget has the focus
+pub struct Ui<'a> {
+    font: Option<&'a Font>,     // None while reacting to inputs, nothing is measured or drawn then
+    view: Vec2,
+    nav: NavInput,
+    focus: usize,
+    focusable: usize,           // seen so far in this pass
+    align: f32,                 // of the items in a column, 0 left to 1 right, from the anchor of the panel
+    size: u16,
+    color: Color,
+    commands: Vec<Command>,
+    containers: Vec<Container>,
+}
+
+impl<'a> Ui<'a> {
+    // draws, nothing reacts to input
+    pub fn new(font: &'a Font, view: Vec2, focus: usize) -> Ui<'a> {
+        Ui::with_parts(Some(font), view, focus, NavInput::default())
+    }
+    // reacts to input, nothing is drawn
+    pub fn interact(view: Vec2, focus: usize, nav: NavInput) -> Ui<'a> {
+        Ui::with_parts(None, view, focus, nav)
+    }
+    fn with_parts(font: Option<&'a Font>, view: Vec2, focus: usize, nav: NavInput) -> Ui<'a> {
+        Ui {
+            font,
+            view,
+            nav,
+            focus,
+            focusable: 0,
+            align: 0f32,
+            size: DEFAULT_SIZE,
+            color: WHITE,
+            commands: Vec::new(),
+            containers: Vec::new(),
+        }
+    }
+    pub fn get_view(&self) -> Vec2 { self.view }
+    pub fn back(&self) -> bool { self.nav.back }
+    // font size and colour of the labels that follow, widgets that take the focus only use the size
+    pub fn set_style(&mut self, size: u16, color: Color) {
+        self.size = size;
+        self.color = color;
+    }
+    // the focus for the next frame, up and down move it and it wraps around
+    pub fn finish(self) -> usize {
+        match self.focusable {
+            0 => 0,
+            count if self.nav.down => (self.focus + 1) % count,
+            count if self.nav.up => (self.focus + count - 1) % count,
+            count => self.focus % count,
+        }
+    }
+
+    // a column of widgets placed against an anchor of the view, offset in playfield units; everything goes into one
+    pub fn anchored(&mut self, anchor: Anchor, offset: Vec2, spacing: f32, contents: impl FnOnce(&mut Ui<'a>)) {
+        self.pinned(anchor, self.view * anchor.factor() + offset, spacing, contents);
+    }
+    // like anchored, but the anchor point of the panel sits on pos instead of on the same point of the view
+    pub fn pinned(&mut self, anchor: Anchor, pos: Vec2, spacing: f32, contents: impl FnOnce(&mut Ui<'a>)) {
+        let factor = anchor.factor();
+        let align = std::mem::replace(&mut self.align, factor.x);
+        self.containers.push(Container { horizontal: false, spacing, items: Vec::new() });
+        contents(self);
+        let item = self.close();
+        self.align = align;
+        self.shift(item.commands.clone(), pos - item.size * factor);
+        self.flush(item.commands.start);
+    }
+    pub fn column(&mut self, spacing: f32, contents: impl FnOnce(&mut Ui<'a>)) {
+        self.containers.push(Container { horizontal: false, spacing, items: Vec::new() });
+        contents(self);
+        let item = self.close();
+        self.push(item);
+    }
+    // items are centred vertically against each other
+    pub fn row(&mut self, spacing: f32, contents: impl FnOnce(&mut Ui<'a>)) {
+        self.containers.push(Container { horizontal: true, spacing, items: Vec::new() });
+        contents(self);
+        let item = self.close();
+        self.push(item);
+    }
+    pub fn space(&mut self, amount: f32) {
+        self.add(Vec::new(), Vec2::splat(amount));
+    }
+    // a filled box behind a column of widgets, padded on every side
+    pub fn panel(&mut self, padding: f32, color: Color, contents: impl FnOnce(&mut Ui<'a>)) {
+        self.containers.push(Container { horizontal: false, spacing: 0f32, items: Vec::new() });
+        contents(self);
+        let item = self.close();
+        self.shift(item.commands.clone(), Vec2::splat(padding));
+        let size = item.size + Vec2::splat(2f32 * padding);
+        // goes in front of the contents so it is drawn below them
+        let start = item.commands.start;
+        self.commands.insert(start, Command::Rect { rect: Rect::new(0f32, 0f32, size.x, size.y), color, filled: true });
+        self.push(Item { commands: start..self.commands.len(), size });
+    }
+
+    pub fn label(&mut self, text: &str) {
+        self.text(text, self.size, self.color);
+    }
+    // one line per item, the highlighted one stands out in its own colour
+    pub fn list(&mut self, items: &[String], highlight: Option<usize>, highlight_color: Color) {
+        let (size, color) = (self.size, self.color);
+        self.column(size as f32 * 0.3f32, |ui| {
+            for (idx, item) in items.iter().enumerate() {
+                ui.text(item, size, if highlight == Some(idx) { highlight_color } else { color });
+            }
+        });
+    }
+    // true when activated
+    pub fn button(&mut self, text: &str) -> bool {
+        let focused = self.take_focus();
+        self.text(text, self.size, if focused { FOCUS_COLOR } else { IDLE_COLOR });
+        focused && self.nav.accept
+    }
+    // a value picked with left and right, the step is -1, 0 or 1; accepting steps forward
+    pub fn choice(&mut self, label: &str, value: &str) -> i32 {
+        let focused = self.take_focus();
+        let (size, color) = (self.size, if focused { FOCUS_COLOR } else { IDLE_COLOR });
+        self.row(size as f32, |ui| {
+            ui.text(label, size, color);
+            ui.text(&format!("< {} >", value), size, color);
+        });
+        match focused {
+            true if self.nav.left => -1,
+            true if self.nav.right || self.nav.accept => 1,
+            _ => 0,
+        }
+    }
+    // returns the value after this frame's inputs, moved by step and kept within min and max
+    pub fn slider(&mut self, label: &str, value: f32, min: f32, max: f32, step: f32) -> f32 {
+        let focused = self.take_focus();
+        let value = match focused {
+            true if self.nav.left => value - step,
+            true if self.nav.right => value + step,
+            _ => value,
+        }.clamp(min, max);
+        let (size, color) = (self.size, if focused { FOCUS_COLOR } else { IDLE_COLOR });
+        let height = size as f32 * 0.5f32;
+        let filled = SLIDER_WIDTH * (value - min) / (max - min).max(f32::EPSILON);
+        self.row(size as f32 * 0.5f32, |ui| {
+            ui.text(label, size, color);
+            ui.add(vec![
+                Command::Rect { rect: Rect::new(0f32, 0f32, filled, height), color, filled: true },
+                Command::Rect { rect: Rect::new(0f32, 0f32, SLIDER_WIDTH, height), color, filled: false },
+            ], Vec2::new(SLIDER_WIDTH, height));
+            ui.text(&format!("{}", value), size, color);
+        });
+        value
+    }
+    // the edited text while it has the focus and something was typed or erased
+    pub fn text_input(&mut self, label: &str, text: &str, max_len: usize) -> Option<String> {
+        let focused = self.take_focus();
+        let mut edited = text.to_string();
+        if focused {
+            for char in self.nav.typed.iter().filter(|char| !char.is_control()) {
+                if edited.chars().count() < max_len { edited.push(*char) }
+            }
+            if self.nav.erase { edited.pop(); }
+        }
+        let (size, color) = (self.size, if focused { FOCUS_COLOR } else { IDLE_COLOR });
+        let shown = if focused { format!("{}_", edited) } else { edited.clone() };
+        self.row(size as f32 * 0.5f32, |ui| {
+            ui.text(label, size, color);
+            ui.text(&shown, size, color);
+        });
+        (edited != text).then_some(edited)
+    }
+
+    fn take_focus(&mut self) -> bool {
+        self.focusable += 1;
+        self.focusable - 1 == self.focus
+    }
+    fn text(&mut self, text: &str, size: u16, color: Color) {
+        let width = match self.font {
+            Some(font) => measure_text(text, Some(font), size, 1f32).width,
+            None => 0f32,
+        };
+        let pos = Vec2::new(0f32, size as f32 * BASELINE);
+        self.add(vec![Command::Text { text: text.to_string(), pos, size, color }], Vec2::new(width, size as f32));
+    }
+    fn add(&mut self, commands: Vec<Command>, size: Vec2) {
+        let start = self.commands.len();
+        self.commands.extend(commands);
+        self.push(Item { commands: start..self.commands.len(), size });
+    }
+    fn push(&mut self, item: Item) {
+        if let Some(container) = self.containers.last_mut() {
+            container.items.push(item);
+        }
+    }
+    // lays out the items of the innermost container, which becomes a single item itself
+    fn close(&mut self) -> Item {
+        let Some(container) = self.containers.pop() else {
+            return Item { commands: self.commands.len()..self.commands.len(), size: Vec2::ZERO };
+        };
+        // x is along the container and y across it, flipped back for columns
+        let horizontal = container.horizontal;
+        let flip = |size: Vec2| if horizontal { size } else { Vec2::new(size.y, size.x) };
+        let cross_size = container.items.iter().map(|item| flip(item.size).y).fold(0f32, f32::max);
+        let align = if horizontal { 0.5f32 } else { self.align };
+        let mut cursor = 0f32;
+        for item in container.items.iter() {
+            let across = (cross_size - flip(item.size).y) * align;
+            self.shift(item.commands.clone(), flip(Vec2::new(cursor, across)));
+            cursor += flip(item.size).x + container.spacing;
+        }
+        let start = container.items.first().map_or(self.commands.len(), |item| item.commands.start);
+        Item {
+            commands: start..self.commands.len(),
+            size: flip(Vec2::new((cursor - container.spacing).max(0f32), cross_size)),
+        }
+    }
+    fn shift(&mut self, commands: Range<usize>, offset: Vec2) {
+        for command in self.commands[commands].iter_mut() {
+            match command {
+                Command::Text { pos, .. } => *pos += offset,
+                Command::Rect { rect, .. } => *rect = rect.offset(offset),
+            }
+        }
+    }
+    // draws and forgets everything from start on
+    fn flush(&mut self, start: usize) {
+        let commands = self.commands.split_off(start);
+        let Some(font) = self.font else { return };
+        for command in commands {
+            match command {
+                Command::Text { text, pos, size, color } => {
+                    draw_text_ex(&text, pos.x, pos.y, TextParams {
+                        font: Option::from(font),
+                        font_size: size,
+                        font_scale: 1f32,
+                        font_scale_aspect: 1f32,
+                        rotation: 0.0,
+                        color,
+                    });
+                }
+                Command::Rect { rect, color, filled: true } => draw_rectangle(rect.x, rect.y, rect.w, rect.h, color),
+                Command::Rect { rect, color, filled: false } => draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2f32, color),
+            }
+        }
+    }
+}
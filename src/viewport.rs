@@ -0,0 +1,104 @@
+use macroquad::camera::{set_camera, set_default_camera, Camera2D};
+use macroquad::color::{Color, BLACK};
+use macroquad::input::mouse_position;
+use macroquad::math::{Rect, Vec2};
+use macroquad::miniquad::window::dpi_scale;
+use macroquad::prelude::{clear_background, draw_rectangle};
+use macroquad::window::{screen_height, screen_width};
+
+// size of the playfield in game units, independent of the window
+pub const PLAYFIELD: Vec2 = Vec2::new(1280f32, 720f32);
+
+const BAR_COLOR: Color = Color::new(0.06f32, 0.06f32, 0.06f32, 1f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    Letterbox,  // keep the aspect ratio, fill the rest with bars
+    Stretch,    // fill the window, distorting the aspect ratio
+    Integer,    // like letterbox but only whole multiples, for crisp lines
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 3] = [ScaleMode::Letterbox, ScaleMode::Stretch, ScaleMode::Integer];
+
+    pub fn from_name(name: &str) -> Option<ScaleMode> {
+        ScaleMode::ALL.into_iter().find(|mode| mode.get_name() == name)
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ScaleMode::Letterbox => "letterbox",
+            ScaleMode::Stretch => "stretch",
+            ScaleMode::Integer => "integer",
+        }
+    }
+    pub fn next(&self) -> ScaleMode {
+        match self {
+            ScaleMode::Letterbox => ScaleMode::Stretch,
+            ScaleMode::Stretch => ScaleMode::Integer,
+            ScaleMode::Integer => ScaleMode::Letterbox,
+        }
+    }
+}
+
+// maps the virtual playfield onto whatever size the window currently has
+pub struct Viewport {
+    size: Vec2,
+    mode: ScaleMode,
+}
+
+impl Viewport {
+    pub fn new(size: Vec2, mode: ScaleMode) -> Viewport {
+        Viewport { size, mode }
+    }
+    pub fn get_size(&self) -> Vec2 { self.size }
+    pub fn get_mode(&self) -> ScaleMode { self.mode }
+    pub fn set_mode(&mut self, mode: ScaleMode) { self.mode = mode }
+
+    // where the playfield ends up on the window, in window pixels; recomputed every call so resizing just works
+    pub fn screen_rect(&self) -> Rect {
+        let screen = Vec2::new(screen_width(), screen_height());
+        let fit = (screen / self.size).min_element();
+        let scale = match self.mode {
+            ScaleMode::Stretch => return Rect::new(0f32, 0f32, screen.x, screen.y),
+            ScaleMode::Letterbox => fit,
+            ScaleMode::Integer if fit >= 1f32 => fit.floor(),
+            ScaleMode::Integer => fit,
+        };
+        let size = self.size * scale;
+        let origin = (screen - size) / 2f32;
+        Rect::new(origin.x, origin.y, size.x, size.y)
+    }
+
+    // clears the window and makes everything drawn until `end` use playfield coordinates
+    pub fn begin(&self) {
+        let rect = self.screen_rect();
+        set_default_camera();
+        clear_background(BAR_COLOR);
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, BLACK);
+        self.look_at(self.size / 2f32);
+    }
+    // moves the view so `centre` is in the middle of the playfield, `begin` resets it for the HUD
+    pub fn look_at(&self, centre: Vec2) {
+        let rect = self.screen_rect();
+        // viewports are in physical pixels, counted from the bottom of the window
+        let dpi = dpi_scale();
+        let y = (screen_height() - rect.y - rect.h) * dpi;
+        set_camera(&Camera2D {
+            target: centre,
+            zoom: Vec2::new(2f32 / self.size.x, 2f32 / self.size.y),
+            viewport: Some(((rect.x * dpi) as i32, y as i32, (rect.w * dpi) as i32, (rect.h * dpi) as i32)),
+            ..Default::default()
+        });
+    }
+    pub fn end(&self) {
+        set_default_camera();
+    }
+
+    pub fn to_playfield(&self, screen_pos: Vec2) -> Vec2 {
+        let rect = self.screen_rect();
+        (screen_pos - rect.point()) / rect.size() * self.size
+    }
+    pub fn mouse_position(&self) -> Vec2 {
+        self.to_playfield(Vec2::from(mouse_position()))
+    }
+}